@@ -12,6 +12,85 @@ use core::fmt::Debug;
 // This should act as user's unique identifier (32 byte hash)
 type IdentityHash = [u8; 32];
 
+// Longest cooldown a trusted contact can ever be allowed to impose
+const MAX_TRUSTED_COOLDOWN_DAYS: u32 = 7;
+
+// Risk indicators. Each one that is present adds RISK_POINTS to the risk score.
+const RISK_POINTS: u8 = 25;
+// Spending today above this multiple of the average daily spend
+const RISK_SPEND_ACCELERATION: u64 = 2;
+// Bets in a row, each with a larger stake than the last
+const RISK_STAKE_INCREASES: u32 = 3;
+// Bets between midnight and 6am (UTC) this month
+const RISK_LATE_NIGHT_BETS: u32 = 5;
+// Distinct platforms bet on today
+const RISK_PLATFORMS_PER_DAY: u32 = 3;
+
+// Prefix of signed affordability attestations, so the verifier's signature on
+// one can never pass as its signature on an account at registration
+const ATTESTATION_DOMAIN: &[u8] = b"SafeStake affordability attestation";
+
+// Days of spending the affordability threshold looks back over, today included
+const AFFORDABILITY_WINDOW_DAYS: u64 = 30;
+
+// Most users prune_expired_exclusions looks at in one call
+const MAX_PRUNE_BATCH: usize = 100;
+
+// Most platform and category exclusions prune_expired_exclusions looks at in
+// one call, across all its users
+const MAX_PRUNE_ENTRIES: usize = 1_000;
+
+// Most users migrate_users converts in one call, so a call stays well inside
+// the energy one transaction may use
+const MAX_MIGRATION_BATCH: u32 = 500;
+
+// Hours between a user asking to return from a self-exclusion and being able
+// to bet again
+const REACTIVATION_WAIT_HOURS: u64 = 24;
+
+// Layout version of State. Bump it whenever the stored layout changes and
+// teach migrate how to convert from the previous version.
+// 1: first tagged layout
+// 2: pause flags
+// 3: role map in place of the regulator set
+// 4: authorized wallets, in State and on each user
+// 5: permit nonces
+// 6: reactivation after a self-exclusion, on each user
+// 7: platform history kept as salted commitments, on each user
+// 8: platforms bet on today, on each user
+// 9: platform history committed with the user's own salt, and platform
+//    accounts only where limits are set
+// 10: spending per day and an affordability attestation nonce, on each user
+// 11: users an upgrade has not converted yet
+const STATE_VERSION: u32 = 11;
+
+// Privileged roles the admin can grant. The admin holds every role.
+#[derive(Serialize, SchemaType, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Role {
+    // May replace the backend verifier's public key
+    VerifierKeyManager,
+    // May register and remove operators
+    OperatorManager,
+    // May exclude users from all platforms
+    Regulator,
+    // May read any user's risk profile and rejected attempts
+    Auditor,
+    // May lower a user's limits at their request
+    Support,
+}
+
+// Entrypoints halted by the admin in an emergency. Safety actions such as
+// self-exclusion and lowering limits are never halted.
+#[derive(Serialize, SchemaType, Clone, Copy, Default, Debug, PartialEq, Eq)]
+pub struct PauseFlags {
+    // Halt register_user
+    pub registration: bool,
+    // Halt record_transaction and record_transactions_batch
+    pub transactions: bool,
+    // Halt set_limits, except for changes that only lower limits
+    pub limits: bool,
+}
+
 // Represents a user's compliance data for responsible gambling
 #[derive(Serial, DeserialWithState)]
 #[concordium(state_parameter = "S")]
@@ -32,24 +111,305 @@ pub struct UserCompliance<S = StateApi> {
     pub last_reset_day: Timestamp,
     // Timestamp of last monthly reset
     pub last_reset_month: Timestamp,
+    // Maximum CCD allowed on a single bet (in microCCD), if the user set one
+    pub max_stake: Option<Amount>,
+    // Maximum number of bets per hour, if the user set one
+    pub max_bets_per_hour: Option<u32>,
+    // Maximum number of bets per day, if the user set one
+    pub max_bets_per_day: Option<u32>,
+    // Bets placed this hour (resets hourly)
+    pub hourly_bets: u32,
+    // Bets placed today (resets daily together with daily_spent)
+    pub daily_bets: u32,
+    // Timestamp of last hourly reset
+    pub last_reset_hour: Timestamp,
+    // Maximum play time per day across all platforms, if the user set one
+    pub daily_time_limit: Option<Duration>,
+    // Play time from sessions that ended today (resets daily)
+    pub daily_play_time: Duration,
+    // Open sessions, mapping platform ID to session start time. An entry is
+    // removed when its session ends.
+    pub active_sessions: StateMap<String, Timestamp, S>,
+    // Per-platform limits and spending, keyed by platform ID. Only platforms
+    // the user has set limits on have an entry, so the other platforms they
+    // use are not named here; spending counts from when the limits are set.
+    pub platform_accounts: StateMap<String, ScopedAccount, S>,
+    // Platforms the user has excluded themselves from, keyed by platform ID
+    pub platform_exclusions: StateMap<String, ScopedExclusion, S>,
+    // Per-category limits and spending, across all platforms
+    pub category_accounts: StateMap<GameCategory, ScopedAccount, S>,
+    // Game categories the user is excluded from
+    pub category_exclusions: StateMap<GameCategory, ScopedExclusion, S>,
     // cooldown period (user is excluded until this time)
     pub cooldown_until: Option<Timestamp>,
-    // Set of platform IDs where user has gambled
-    pub platforms_used: StateSet<String, S>,
+    // Who excluded the user from all platforms and why, while excluded
+    pub exclusion_source: Option<ExclusionSource>,
+    // Set when the user excludes themselves from all platforms. Once that
+    // exclusion ends they have to request reactivation before betting again.
+    pub reactivation_required: bool,
+    // When the user requested reactivation, if they have
+    pub reactivation_requested_at: Option<Timestamp>,
+    // Nominated trusted contacts, mapping contact to the longest cooldown in
+    // days the user consented to them imposing
+    pub trusted_contacts: StateMap<AccountAddress, u32, S>,
+    // Smart contract wallet allowed to set limits and self-exclude for the user
+    pub wallet: Option<ContractAddress>,
+    // User's rule for starting a cooldown automatically, if they set one
+    pub auto_cooldown: Option<AutoCooldownRule>,
+    // Bets rejected by a limit since rejection_window_start
+    pub rejected_attempts: u32,
+    // Start of the window rejected_attempts is counted in
+    pub rejection_window_start: Timestamp,
+    // Bets rejected this hour (resets hourly together with hourly_bets)
+    pub hourly_rejections: u32,
+    // Bets rejected today (resets daily together with daily_bets)
+    pub daily_rejections: u32,
+    // Time of the most recent rejected bet
+    pub last_rejection: Option<Timestamp>,
+    // Behavioural risk indicators, updated on every recorded bet
+    pub risk: RiskProfile,
+    // End of the verifier's latest affordability attestation, if any
+    pub affordability_attested_until: Option<Timestamp>,
+    // Nonce the verifier's next affordability attestation must use
+    pub affordability_nonce: u64,
+    // Spending per UTC day over the last AFFORDABILITY_WINDOW_DAYS days, keyed
+    // by the day's number since the epoch
+    pub spent_by_day: StateMap<u64, Amount, S>,
+    // Commitments sha256(salt || platform ID) to the platforms where the user
+    // has gambled, as passed by the operators. The salt is the user's own and
+    // never reaches the contract, so the stored list does not name platforms.
+    // It does not hide them from the chain: each bet is sent by its platform's
+    // operator, so the transaction history still shows where the user bets.
+    // The contract cannot check a commitment without the salt, so this count
+    // and risk.platforms_today rely on operators passing the commitment the
+    // user's wallet gave them.
+    pub platforms_used: StateSet<[u8; 32], S>,
+    // Number of distinct platforms where the user has gambled
+    pub platforms_used_count: u32,
+    // Commitments to the platforms the user has bet on today (resets daily),
+    // so risk.platforms_today counts each platform once
+    pub platforms_bet_today: StateSet<[u8; 32], S>,
     // Age verification status
     pub age_verified: bool,
 }
 
+// Kind of game a bet is placed on, so users can restrict high-intensity
+// products (e.g. slots) while keeping others (e.g. lottery) available
+#[derive(Serialize, SchemaType, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum GameCategory {
+    Sports,
+    Casino,
+    Slots,
+    Poker,
+    Lottery,
+    Bingo,
+    Other,
+}
+
+// Betting patterns associated with gambling harm. The contract does not learn
+// whether bets win or lose, so loss chasing shows up as rising stakes.
+#[derive(Serialize, SchemaType, Clone, Copy, Debug, PartialEq, Eq)]
+pub struct RiskProfile {
+    // Average daily spend over recent days with betting activity
+    pub average_daily_spend: Amount,
+    // Stake of the last recorded bet
+    pub last_stake: Amount,
+    // Bets in a row with a larger stake than the one before
+    pub stake_increases: u32,
+    // Bets between midnight and 6am (UTC) this month (resets monthly)
+    pub late_night_bets: u32,
+    // Distinct platforms bet on today (resets daily)
+    pub platforms_today: u32,
+    // Risk score from 0 to 100 as of the last recorded bet
+    pub score: u8,
+}
+
+// When to put the user on a cooldown without them asking
+#[derive(Serialize, SchemaType, Clone, Copy, Debug, PartialEq, Eq)]
+pub struct AutoCooldownRule {
+    // Length of the cooldown
+    pub cooldown: Duration,
+    // Start the cooldown when a bet uses up the daily or monthly limit
+    pub on_limit_reached: bool,
+    // Start the cooldown after this many bets are rejected by a limit within
+    // rejection_window (None to ignore rejected bets)
+    pub max_rejections: Option<u32>,
+    // Window in which rejected bets are counted
+    pub rejection_window: Duration,
+}
+
+// Who put an exclusion in place
+#[derive(Serialize, SchemaType, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ExclusionIssuer {
+    // The user excluded themselves
+    User,
+    // A registered operator, acting under its duty of care
+    Operator(AccountAddress),
+    // A regulator, e.g. enforcing a court order
+    Regulator(AccountAddress),
+    // A trusted contact nominated by the user
+    TrustedContact(AccountAddress),
+}
+
+// Reason code recorded with an exclusion
+#[derive(Serialize, SchemaType, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ExclusionReason {
+    // Requested by the user
+    UserRequest,
+    // Operator observed markers of harm
+    HarmMarkers,
+    // Spending is not affordable for the user
+    Affordability,
+    // Ordered by a court
+    CourtOrder,
+    // Ordered by a regulator
+    RegulatoryOrder,
+    // Any other reason the issuer gives
+    Other,
+    // Cooling-off requested by a trusted contact
+    TrustedContactRequest,
+    // Started by the user's own automatic cooldown rule
+    AutoCooldown,
+}
+
+// Issuer and reason of an exclusion
+#[derive(Serialize, SchemaType, Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ExclusionSource {
+    pub issuer: ExclusionIssuer,
+    pub reason: ExclusionReason,
+}
+
+// Exclusion from a single platform or game category
+#[derive(Serialize, SchemaType, Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ScopedExclusion {
+    // End of the exclusion
+    pub until: Timestamp,
+    // Who imposed it and why
+    pub source: ExclusionSource,
+}
+
+// A user's limits and spending on a single platform or game category.
+// Spending is stamped with the user's reset times it was recorded under, so it
+// resets together with the global daily/monthly spending without touching
+// every account.
+#[derive(Serialize, SchemaType, Clone, Debug, PartialEq, Eq)]
+pub struct ScopedAccount {
+    // Maximum CCD allowed to spend per day in this scope (in microCCD)
+    pub daily_limit: Option<Amount>,
+    // Maximum CCD allowed to spend per month in this scope (in microCCD)
+    pub monthly_limit: Option<Amount>,
+    // Amount spent in this scope since day_started
+    pub daily_spent: Amount,
+    // Amount spent in this scope since month_started
+    pub monthly_spent: Amount,
+    // User's last_reset_day when daily_spent was recorded
+    pub day_started: Timestamp,
+    // User's last_reset_month when monthly_spent was recorded
+    pub month_started: Timestamp,
+}
+
+impl UserCompliance {
+    // Fresh record with no limits set and all counters starting now
+    fn new(
+        identity_hash: IdentityHash,
+        age_verified: bool,
+        current_time: Timestamp,
+        state_builder: &mut StateBuilder,
+    ) -> Self {
+        UserCompliance {
+            identity_hash,
+            daily_limit: Amount::zero(),
+            monthly_limit: Amount::zero(),
+            daily_spent: Amount::zero(),
+            monthly_spent: Amount::zero(),
+            last_reset_day: current_time,
+            last_reset_month: current_time,
+            max_stake: None,
+            max_bets_per_hour: None,
+            max_bets_per_day: None,
+            hourly_bets: 0,
+            daily_bets: 0,
+            last_reset_hour: current_time,
+            daily_time_limit: None,
+            daily_play_time: Duration::from_millis(0),
+            active_sessions: state_builder.new_map(),
+            platform_accounts: state_builder.new_map(),
+            platform_exclusions: state_builder.new_map(),
+            category_accounts: state_builder.new_map(),
+            category_exclusions: state_builder.new_map(),
+            cooldown_until: None,
+            exclusion_source: None,
+            reactivation_required: false,
+            reactivation_requested_at: None,
+            trusted_contacts: state_builder.new_map(),
+            wallet: None,
+            auto_cooldown: None,
+            rejected_attempts: 0,
+            rejection_window_start: current_time,
+            hourly_rejections: 0,
+            daily_rejections: 0,
+            last_rejection: None,
+            risk: RiskProfile {
+                average_daily_spend: Amount::zero(),
+                last_stake: Amount::zero(),
+                stake_increases: 0,
+                late_night_bets: 0,
+                platforms_today: 0,
+                score: 0,
+            },
+            affordability_attested_until: None,
+            affordability_nonce: 0,
+            spent_by_day: state_builder.new_map(),
+            platforms_used: state_builder.new_set(),
+            platforms_used_count: 0,
+            platforms_bet_today: state_builder.new_set(),
+            age_verified,
+        }
+    }
+}
+
 // state of the contract
 #[derive(Serial, DeserialWithState)]
 #[concordium(state_parameter = "S")]
 pub struct State<S = StateApi> {
+    // Layout version, always stored first so migrate can read it from any layout
+    version: u32,
     // Registry mapping identity hashes to user compliance data
     registry: StateMap<IdentityHash, UserCompliance<S>, S>,
     // Set of users who have self-excluded
     excluded_users: StateSet<IdentityHash, S>,
     // Backend verifier's public key for signature verification
     verifier_key: PublicKeyEd25519,
+    // Account allowed to change contract-wide settings
+    admin: AccountAddress,
+    // Maximum stake on a single bet for every user (e.g. a regulatory cap)
+    stake_cap: Option<Amount>,
+    // Registered operator accounts, mapping operator to its platform ID
+    operators: StateMap<AccountAddress, String, S>,
+    // Wallet contracts authorized by users, mapping wallet to its user
+    wallets: StateMap<ContractAddress, AccountAddress, S>,
+    // Nonce each account's next permit message must use
+    permit_nonces: StateMap<AccountAddress, u64, S>,
+    // Roles granted to accounts other than the admin
+    roles: StateSet<(AccountAddress, Role), S>,
+    // Risk score at which check_eligibility asks for a review, if set
+    risk_review_threshold: Option<u8>,
+    // Spend in the last 30 days above which a user's bets need an
+    // affordability attestation, if set
+    affordability_threshold: Option<Amount>,
+    // Entrypoints currently halted
+    paused: PauseFlags,
+    // Users still stored in an older layout after an upgrade, if any
+    pending_users: Option<PendingUsers>,
+}
+
+// Users an upgrade left in their old layout for migrate_users to convert
+#[derive(Serialize, Clone, Copy)]
+struct PendingUsers {
+    // Layout version the users are stored in
+    version: u32,
+    // Prefix of the old registry, which holds one entry per user
+    prefix: [u8; 8],
 }
 
 // Custom errors 
@@ -74,6 +434,173 @@ pub enum ContractError {
     InvalidSignature,
     // User has not completed age verification
     AgeNotVerified,
+    // Sender is not allowed to call this function
+    Unauthorized,
+    // Bet is larger than the maximum allowed stake
+    StakeTooHigh,
+    // User has placed the maximum number of bets for this hour or day
+    BetCountLimitExceeded,
+    // User has used up their daily play time
+    SessionTimeLimitExceeded,
+    // User already has an open session on this platform
+    SessionAlreadyActive,
+    // User has no open session on this platform
+    SessionNotFound,
+    // User has exceeded their daily spending limit on this platform
+    PlatformDailyLimitExceeded,
+    // User has exceeded their monthly spending limit on this platform
+    PlatformMonthlyLimitExceeded,
+    // User is excluded from this platform
+    PlatformExcluded,
+    // User has exceeded their daily spending limit on this game category
+    CategoryDailyLimitExceeded,
+    // User has exceeded their monthly spending limit on this game category
+    CategoryMonthlyLimitExceeded,
+    // User is excluded from this game category
+    CategoryExcluded,
+    // Failed logging: log is full
+    LogFull,
+    // Failed logging: log is malformed
+    LogMalformed,
+    // Cooldown is longer than the user allowed
+    CooldownTooLong,
+    // Spend in the last 30 days would pass the affordability threshold without
+    // a valid attestation
+    AffordabilityCheckRequired,
+    // Attestation has already expired
+    AttestationExpired,
+    // Upgrade failed because the new module does not exist
+    FailedUpgradeMissingModule,
+    // Upgrade failed because the new module has no safestake_registry contract
+    FailedUpgradeMissingContract,
+    // Upgrade failed because the new module's smart contract version is not supported
+    FailedUpgradeUnsupportedModuleVersion,
+    // Calling migrate after the upgrade failed
+    MigrationFailed,
+    // Stored state has a layout version this module cannot migrate from
+    UnsupportedStateVersion,
+    // This entrypoint is halted by the admin
+    Paused,
+    // Sender is a contract that is not an authorized wallet
+    ContractSenderNotAllowed,
+    // Wallet contract is already authorized by another user
+    WalletInUse,
+    // Permit message has expired
+    PermitExpired,
+    // Permit or attestation does not use the next nonce
+    NonceMismatch,
+    // Adding up amounts went past the largest representable amount
+    AmountOverflow,
+    // Too many entries in one call
+    BatchTooLarge,
+    // Self-exclusion has ended but the user has not completed reactivation
+    PendingReactivation,
+    // User has no ended self-exclusion to return from
+    ReactivationNotRequired,
+    // User has already registered with age verification
+    UserAlreadyRegistered,
+    // Users from before an upgrade have not all been converted yet
+    MigrationPending,
+}
+
+impl From<LogError> for ContractError {
+    fn from(le: LogError) -> Self {
+        match le {
+            LogError::Full => ContractError::LogFull,
+            LogError::Malformed => ContractError::LogMalformed,
+        }
+    }
+}
+
+impl From<UpgradeError> for ContractError {
+    fn from(ue: UpgradeError) -> Self {
+        match ue {
+            UpgradeError::MissingModule => ContractError::FailedUpgradeMissingModule,
+            UpgradeError::MissingContract => ContractError::FailedUpgradeMissingContract,
+            UpgradeError::UnsupportedModuleVersion => {
+                ContractError::FailedUpgradeUnsupportedModuleVersion
+            }
+        }
+    }
+}
+
+impl<T> From<CallContractError<T>> for ContractError {
+    fn from(_: CallContractError<T>) -> Self {
+        ContractError::MigrationFailed
+    }
+}
+
+// Where an exclusion applies
+#[derive(Serialize, SchemaType, Clone, Debug, PartialEq, Eq)]
+pub enum ExclusionScope {
+    // Every platform
+    Global,
+    // A single platform
+    Platform(String),
+    // A single game category on every platform
+    Category(GameCategory),
+}
+
+// Details of a newly imposed exclusion
+#[derive(Serialize, SchemaType, Debug, PartialEq, Eq)]
+pub struct ExclusionEvent {
+    // Excluded user's account address
+    pub user_account: AccountAddress,
+    // Where the exclusion applies
+    pub scope: ExclusionScope,
+    // End of the exclusion (None if indefinite)
+    pub until: Option<Timestamp>,
+    // Who imposed it and why
+    pub source: ExclusionSource,
+}
+
+// Details of a bet the contract refused to record. The platform is the one
+// whose operator sent the transaction.
+#[derive(Serialize, SchemaType, Debug, PartialEq, Eq)]
+pub struct BetRejectedEvent {
+    // User's account address
+    pub user_account: AccountAddress,
+    // Amount of the bet in microCCD
+    pub amount: Amount,
+    // Why the bet was refused
+    pub outcome: TransactionOutcome,
+}
+
+// Details of an exclusion that has run out and been removed
+#[derive(Serialize, SchemaType, Debug, PartialEq, Eq)]
+pub struct ExclusionEndedEvent {
+    // User's account address
+    pub user_account: AccountAddress,
+    // Where the exclusion applied
+    pub scope: ExclusionScope,
+    // Whether the user must request reactivation before betting again
+    pub reactivation_required: bool,
+}
+
+// Details of a user's request to return from a self-exclusion
+#[derive(Serialize, SchemaType, Debug, PartialEq, Eq)]
+pub struct ReactivationEvent {
+    // User's account address
+    pub user_account: AccountAddress,
+    // When the user may bet again
+    pub reactivates_at: Timestamp,
+}
+
+// Events logged by the contract
+#[derive(Serialize, SchemaType, Debug, PartialEq, Eq)]
+pub enum Event {
+    // A user was excluded from all platforms, a platform or a game category
+    ExclusionImposed(ExclusionEvent),
+    // A bet by a registered user was refused
+    BetRejected(BetRejectedEvent),
+    // An account was given a role
+    RoleGranted(RoleParams),
+    // An account lost a role
+    RoleRevoked(RoleParams),
+    // An exclusion ended
+    ExclusionEnded(ExclusionEndedEvent),
+    // A user asked to return from a self-exclusion
+    ReactivationRequested(ReactivationEvent),
 }
 
  // Eligibility status for placing bets
@@ -93,6 +620,50 @@ pub enum EligibilityStatus {
     NotRegistered,
     // User has not verified their age
     AgeNotVerified,
+    // Proposed bet is larger than the maximum allowed stake
+    StakeTooHigh,
+    // User has placed the maximum number of bets for this hour or day
+    BetCountLimitReached,
+    // User has used up their daily play time
+    SessionTimeLimitReached,
+    // User would exceed their daily limit on the requested platform
+    PlatformDailyLimitReached,
+    // User would exceed their monthly limit on the requested platform
+    PlatformMonthlyLimitReached,
+    // User is excluded from the requested platform
+    PlatformExcluded,
+    // User would exceed their daily limit on the requested game category
+    CategoryDailyLimitReached,
+    // User would exceed their monthly limit on the requested game category
+    CategoryMonthlyLimitReached,
+    // User is excluded from the requested game category
+    CategoryExcluded,
+    // User's risk score calls for a review. Advisory only: record_transaction
+    // still records the bet, so operators decide what a review means for them.
+    ReviewRequired,
+    // Bet would pass the affordability threshold without a valid attestation
+    AffordabilityCheckRequired,
+    // User's self-exclusion has ended but they have not completed reactivation
+    PendingReactivation,
+    // Recording of bets is paused by the admin
+    TransactionsPaused,
+}
+
+// Eligibility answer with the details an operator needs to explain it to the user
+#[derive(Serialize, SchemaType, Debug, PartialEq, Eq)]
+pub struct EligibilityResponse {
+    // Eligibility for the proposed amount
+    pub status: EligibilityStatus,
+    // Amount the user can still spend today in microCCD
+    pub remaining_daily: Amount,
+    // Amount the user can still spend this month in microCCD
+    pub remaining_monthly: Amount,
+    // Largest bet that would currently be eligible in microCCD
+    pub max_eligible_stake: Amount,
+    // When the blocking condition ends (next reset or end of cooldown), if it ends
+    pub blocked_until: Option<Timestamp>,
+    // Who imposed the exclusion and why, if the user is blocked by one
+    pub exclusion: Option<ExclusionSource>,
 }
 
 // Parameter for initializing the contract with verifier's public key
@@ -109,6 +680,146 @@ pub struct SelfExcludeParams {
     pub duration_days: u32,
 }
 
+// Parameter for self-exclusion from specific platforms only
+#[derive(Serialize, SchemaType)]
+pub struct SelfExcludePlatformsParams {
+    // Platforms to exclude from
+    pub platform_ids: Vec<String>,
+    // Duration in days
+    pub duration_days: u32,
+}
+
+// Parameter for self-exclusion from specific game categories only
+#[derive(Serialize, SchemaType)]
+pub struct SelfExcludeCategoriesParams {
+    // Game categories to exclude from
+    pub categories: Vec<GameCategory>,
+    // Duration in days
+    pub duration_days: u32,
+}
+
+// Parameter for an operator excluding a user from its own platform
+#[derive(Serialize, SchemaType)]
+pub struct ImposePlatformExclusionParams {
+    // User's account address
+    pub user_account: AccountAddress,
+    // Duration in days
+    pub duration_days: u32,
+    // Reason code
+    pub reason: ExclusionReason,
+}
+
+// Parameter for a regulator excluding a user from all platforms
+#[derive(Serialize, SchemaType)]
+pub struct ImposeExclusionParams {
+    // User's account address
+    pub user_account: AccountAddress,
+    // Duration in days (None for an indefinite exclusion)
+    pub duration_days: Option<u32>,
+    // Reason code
+    pub reason: ExclusionReason,
+}
+
+// Parameter for setting the calling user's automatic cooldown rule
+#[derive(Serialize, SchemaType)]
+pub struct SetAutoCooldownParams {
+    // Rule to apply (None to remove it)
+    pub rule: Option<AutoCooldownRule>,
+}
+
+// Parameter for nominating or removing a trusted contact
+#[derive(Serialize, SchemaType)]
+pub struct SetTrustedContactParams {
+    // Trusted contact's account address
+    pub contact: AccountAddress,
+    // Longest cooldown in days the contact may impose, at most 7 (None to remove
+    // the contact)
+    pub max_cooldown_days: Option<u32>,
+}
+
+// Parameter for a trusted contact imposing a cooldown on a user
+#[derive(Serialize, SchemaType)]
+pub struct TrustedCooldownParams {
+    // User's account address
+    pub user_account: AccountAddress,
+    // Duration in days
+    pub duration_days: u32,
+}
+
+// Parameter for registering or removing an operator
+#[derive(Serialize, SchemaType)]
+pub struct SetOperatorParams {
+    // Operator's account address
+    pub operator: AccountAddress,
+    // Platform the operator runs (None to remove the operator)
+    pub platform_id: Option<String>,
+}
+
+// Action a user authorizes in a permit message
+#[derive(Serialize, SchemaType)]
+pub enum PermitAction {
+    // Same as calling set_limits
+    SetLimits(SetLimitsParams),
+    // Same as calling self_exclude
+    SelfExclude(SelfExcludeParams),
+}
+
+// Message a user signs to let someone else submit an action for them
+#[derive(Serialize, SchemaType)]
+pub struct PermitMessage {
+    // This contract, so the message cannot be used on another instance
+    pub contract_address: ContractAddress,
+    // Signer's next permit nonce, so the message can be used only once
+    pub nonce: u64,
+    // Time after which the message can no longer be used
+    pub expiry: Timestamp,
+    // What the user authorizes
+    pub action: PermitAction,
+}
+
+// Parameter for submitting a signed permit message
+#[derive(Serialize, SchemaType)]
+pub struct PermitParams {
+    // User's account address
+    pub signer: AccountAddress,
+    // Signatures with the user's account keys
+    pub signature: AccountSignatures,
+    // The signed message
+    pub message: PermitMessage,
+}
+
+// Parameter for authorizing a wallet contract
+#[derive(Serialize, SchemaType)]
+pub struct SetWalletParams {
+    // Wallet contract to act for the user (None to remove the current one)
+    pub wallet: Option<ContractAddress>,
+}
+
+// Parameter for granting or revoking a role
+#[derive(Serialize, SchemaType, Debug, PartialEq, Eq)]
+pub struct RoleParams {
+    // Account receiving or losing the role
+    pub account: AccountAddress,
+    // The role
+    pub role: Role,
+}
+
+// Parameter for replacing the backend verifier's public key
+#[derive(Serialize, SchemaType)]
+pub struct SetVerifierKeyParams {
+    // New public key used to check age verification and attestation signatures
+    pub verifier_key: PublicKeyEd25519,
+}
+
+// Parameter for support staff lowering a user's limits at their request
+#[derive(Serialize, SchemaType)]
+pub struct SupportLowerLimitsParams {
+    // User's account address
+    pub user_account: AccountAddress,
+    // New limits, each no higher than the user's current one
+    pub limits: SetLimitsParams,
+}
+
 // Parameter for registering a new user with age verification
 #[derive(Serialize, SchemaType)]
 pub struct RegisterUserParams {
@@ -127,19 +838,222 @@ pub struct SetLimitsParams {
     // Monthly spending limit in microCCD
     //TODO: update contract to use stablecoin instead of CCD
     pub monthly_limit: Amount,
+    // Maximum stake on a single bet in microCCD (None for no per-bet limit)
+    pub max_stake: Option<Amount>,
+    // Maximum number of bets per hour (None for no limit)
+    pub max_bets_per_hour: Option<u32>,
+    // Maximum number of bets per day (None for no limit)
+    pub max_bets_per_day: Option<u32>,
+    // Maximum play time per day across all platforms (None for no limit)
+    pub daily_time_limit: Option<Duration>,
+}
+
+// Parameter for setting the calling user's limits on one platform
+#[derive(Serialize, SchemaType)]
+pub struct SetPlatformLimitsParams {
+    // Platform identifier
+    pub platform_id: String,
+    // Daily spending limit on this platform in microCCD (None for no limit)
+    pub daily_limit: Option<Amount>,
+    // Monthly spending limit on this platform in microCCD (None for no limit)
+    pub monthly_limit: Option<Amount>,
+}
+
+// Parameter for setting the calling user's limits on one game category
+#[derive(Serialize, SchemaType)]
+pub struct SetCategoryLimitsParams {
+    // Game category
+    pub category: GameCategory,
+    // Daily spending limit on this category in microCCD (None for no limit)
+    pub daily_limit: Option<Amount>,
+    // Monthly spending limit on this category in microCCD (None for no limit)
+    pub monthly_limit: Option<Amount>,
+}
+
+// Parameter for setting the risk score that requires a review
+#[derive(Serialize, SchemaType)]
+pub struct SetRiskThresholdParams {
+    // Risk score from 0 to 100 (None to never require a review)
+    pub threshold: Option<u8>,
+}
+
+// Parameter for setting the affordability threshold
+#[derive(Serialize, SchemaType)]
+pub struct SetAffordabilityThresholdParams {
+    // Spend in the last 30 days in microCCD (None to never require an attestation)
+    pub threshold: Option<Amount>,
+}
+
+// Parameter for upgrading the contract to a new module
+#[derive(Serialize, SchemaType)]
+pub struct UpgradeParams {
+    // Module to upgrade to
+    pub module: ModuleReference,
+    // Entrypoint and parameter to call on the upgraded contract, usually migrate
+    pub migrate: Option<(OwnedEntrypointName, OwnedParameter)>,
+}
+
+// Parameter for submitting the verifier's affordability attestation for a user
+#[derive(Serialize, SchemaType)]
+pub struct AffordabilityAttestationParams {
+    // User's account address
+    pub account: AccountAddress,
+    // User's next attestation nonce, so an attestation can be used only once
+    pub nonce: u64,
+    // End of the attestation
+    pub valid_until: Timestamp,
+    // Verifier's signature, see submit_affordability_attestation
+    pub signature: SignatureEd25519,
+}
+
+// Parameter for setting the contract-wide maximum stake
+#[derive(Serialize, SchemaType)]
+pub struct SetStakeCapParams {
+    // Maximum stake on a single bet in microCCD (None to remove the cap)
+    pub stake_cap: Option<Amount>,
 }
 
-// Parameter for recording a transaction
+// Parameter for recording a transaction on the calling operator's platform
 #[derive(Serialize, SchemaType)]
 pub struct RecordTransactionParams {
     // User's account address
     pub user_account: AccountAddress,
     // Amount of the bet in microCCD
     pub amount: Amount,
+    // Commitment sha256(salt || platform ID) with the user's own salt, which
+    // the user's wallet gives the operator (not checked by the contract)
+    pub platform_commitment: [u8; 32],
+    // Kind of game the bet is placed on
+    pub category: GameCategory,
+}
+
+// A single bet inside a batch of transactions
+#[derive(Serialize, SchemaType)]
+pub struct BatchTransactionEntry {
+    // User's account address
+    pub user_account: AccountAddress,
+    // Amount of the bet in microCCD
+    pub amount: Amount,
+    // Commitment sha256(salt || platform ID) with the user's own salt, which
+    // the user's wallet gives the operator
+    pub platform_commitment: [u8; 32],
+    // Kind of game the bet is placed on
+    pub category: GameCategory,
+    // Operator's identifier for the bet, echoed back in the result
+    pub bet_id: u64,
+}
+
+// Parameter for recording many transactions on the calling operator's
+// platform at once
+#[derive(Serialize, SchemaType)]
+pub struct RecordTransactionsBatchParams {
+    // Bets to record, applied in order
+    pub entries: Vec<BatchTransactionEntry>,
+}
+
+// Outcome of recording a single bet
+#[derive(Serialize, SchemaType, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum TransactionOutcome {
+    // Bet was recorded against the user's limits
+    Recorded,
+    // Bet would exceed the daily limit
+    DailyLimitExceeded,
+    // Bet would exceed the monthly limit
+    MonthlyLimitExceeded,
+    // User is self-excluded
+    SelfExcluded,
+    // User is on cooldown
+    OnCooldown,
+    // User not registered
+    NotRegistered,
+    // User has not verified their age
+    AgeNotVerified,
+    // Bet is larger than the maximum allowed stake
+    StakeTooHigh,
+    // User has placed the maximum number of bets for this hour or day
+    BetCountLimitExceeded,
+    // User has used up their daily play time
+    SessionTimeLimitExceeded,
+    // Bet would exceed the daily limit on this platform
+    PlatformDailyLimitExceeded,
+    // Bet would exceed the monthly limit on this platform
+    PlatformMonthlyLimitExceeded,
+    // User is excluded from this platform
+    PlatformExcluded,
+    // Bet would exceed the daily limit on this game category
+    CategoryDailyLimitExceeded,
+    // Bet would exceed the monthly limit on this game category
+    CategoryMonthlyLimitExceeded,
+    // User is excluded from this game category
+    CategoryExcluded,
+    // Bet would pass the affordability threshold without a valid attestation
+    AffordabilityCheckRequired,
+    // User's self-exclusion has ended but they have not completed reactivation
+    PendingReactivation,
+}
+
+// Per-entry result of a batch, in the same order as the input entries
+#[derive(Serialize, SchemaType, Debug, PartialEq, Eq)]
+pub struct BatchTransactionResult {
+    // Operator's identifier for the bet
+    pub bet_id: u64,
+    // What happened to the bet
+    pub outcome: TransactionOutcome,
+}
+
+// Parameter for starting or ending a play session
+#[derive(Serialize, SchemaType)]
+pub struct SessionParams {
+    // User's account address
+    pub user_account: AccountAddress,
     // Platform identifier
     pub platform_id: String,
 }
 
+// Rejected bets of a user, a signal of trying to gamble past their limits
+#[derive(Serialize, SchemaType, Debug, PartialEq, Eq)]
+pub struct RejectedAttemptsInfo {
+    // Bets rejected this hour
+    pub this_hour: u32,
+    // Bets rejected today
+    pub today: u32,
+    // Time of the most recent rejected bet, if any
+    pub last_rejected_at: Option<Timestamp>,
+}
+
+// A user's exclusions that are currently in force
+#[derive(Serialize, SchemaType, Debug, PartialEq, Eq)]
+pub struct ActiveExclusions {
+    // Exclusion from all platforms as (end, source); the end is None while it
+    // is indefinite, and the source is None for an exclusion taken out before
+    // the user registered
+    pub global: Option<(Option<Timestamp>, Option<ExclusionSource>)>,
+    // Exclusions from single platforms
+    pub platforms: Vec<(String, ScopedExclusion)>,
+    // Exclusions from single game categories
+    pub categories: Vec<(GameCategory, ScopedExclusion)>,
+}
+
+// A user's platform history as commitments, opened with the user's salt
+#[derive(Serialize, SchemaType, Debug, PartialEq, Eq)]
+pub struct PlatformHistory {
+    // Commitments sha256(salt || platform ID), one per platform used
+    pub commitments: Vec<[u8; 32]>,
+    // Number of distinct platforms used
+    pub count: u32,
+}
+
+// Play time summary used by operators for reality checks
+#[derive(Serialize, SchemaType, Debug, PartialEq, Eq)]
+pub struct PlayTimeInfo {
+    // Play time today across all platforms, including open sessions
+    pub play_time_today: Duration,
+    // User's daily play time limit, if any
+    pub daily_time_limit: Option<Duration>,
+    // Open sessions as (platform ID, start time)
+    pub active_sessions: Vec<(String, Timestamp)>,
+}
+
 // Parameter for checking eligibility
 #[derive(Serialize, SchemaType)]
 pub struct CheckEligibilityParams {
@@ -147,6 +1061,33 @@ pub struct CheckEligibilityParams {
     pub user_account: AccountAddress,
     // Proposed bet amount in microCCD
     pub proposed_amount: Amount,
+    // Platform the bet would be placed on, to include its per-platform limits
+    pub platform_id: Option<String>,
+    // Kind of game the bet would be placed on, to include its category limits
+    pub category: Option<GameCategory>,
+}
+
+// Parameter for removing expired exclusions
+#[derive(Serialize, SchemaType)]
+pub struct PruneExclusionsParams {
+    // Users whose expired exclusions to remove (at most 100)
+    pub users: Vec<AccountAddress>,
+}
+
+// Parameter for checking eligibility of many users at once
+#[derive(Serialize, SchemaType)]
+pub struct CheckEligibilityBatchParams {
+    // One eligibility check per user
+    pub checks: Vec<CheckEligibilityParams>,
+}
+
+// Eligibility of a single user in a batch check
+#[derive(Serialize, SchemaType, Debug, PartialEq, Eq)]
+pub struct EligibilityBatchEntry {
+    // User's account address
+    pub user_account: AccountAddress,
+    // Eligibility for the proposed amount
+    pub eligibility: EligibilityResponse,
 }
 
 // Initialize the contract with verifier's public key.
@@ -155,12 +1096,35 @@ fn init(ctx: &InitContext, state_builder: &mut StateBuilder) -> InitResult<State
     let params: InitParams = ctx.parameter_cursor().get()?;
     
     Ok(State {
+        version: STATE_VERSION,
         registry: state_builder.new_map(),
         excluded_users: state_builder.new_set(),
         verifier_key: params.verifier_key,
+        admin: ctx.init_origin(),
+        stake_cap: None,
+        operators: state_builder.new_map(),
+        wallets: state_builder.new_map(),
+        permit_nonces: state_builder.new_map(),
+        roles: state_builder.new_set(),
+        risk_review_threshold: None,
+        affordability_threshold: None,
+        paused: PauseFlags::default(),
+        pending_users: None,
     })
 }
 
+// Largest single bet the user may place, combining their own limit with the
+// contract-wide cap
+fn effective_max_stake(
+    user_max_stake: Option<Amount>,
+    stake_cap: Option<Amount>,
+) -> Option<Amount> {
+    match (user_max_stake, stake_cap) {
+        (Some(user_max), Some(cap)) => Some(user_max.min(cap)),
+        (user_max, cap) => user_max.or(cap),
+    }
+}
+
 // Helper function to hash an account address to create a deterministic identity hash.
 fn hash_account(account: AccountAddress) -> IdentityHash {
     *account.as_ref()
@@ -182,8 +1146,13 @@ fn register_user(
     host: &mut Host<State>,
     crypto_primitives: &impl HasCryptoPrimitives,
 ) -> Result<(), ContractError> {
+    ensure_users_migrated(host.state())?;
     let params: RegisterUserParams = ctx.parameter_cursor().get()?;
     
+    if host.state().paused.registration {
+        return Err(ContractError::Paused);
+    }
+    
     // Verify the signature from the backend verifier
     // The message signed is the user's account address (32 bytes)
     let message = params.account.as_ref();
@@ -203,222 +1172,2863 @@ fn register_user(
     let identity_hash = hash_account(params.account);
     let current_time = ctx.metadata().slot_time();
     
-    let user_compliance = UserCompliance {
-        identity_hash,
-        daily_limit: Amount::zero(),
-        monthly_limit: Amount::zero(),
-        daily_spent: Amount::zero(),
-        monthly_spent: Amount::zero(),
-        last_reset_day: current_time,
-        last_reset_month: current_time,
-        cooldown_until: None,
-        platforms_used: host.state_builder().new_set(),
-        age_verified: true,  // Mark as age-verified
-    };
+    // An existing record keeps its limits and exclusions, which the user must
+    // not be able to reset by registering again
+    if let Some(mut user) = host.state_mut().registry.get_mut(&identity_hash) {
+        if user.age_verified {
+            return Err(ContractError::UserAlreadyRegistered);
+        }
+        user.age_verified = true;
+        return Ok(());
+    }
+    
+    let user_compliance =
+        UserCompliance::new(identity_hash, true, current_time, host.state_builder());
     
     let _ = host.state_mut().registry.insert(identity_hash, user_compliance);
     Ok(())
 }
 
-// Set spending limits for the calling user
+// Account the sender acts for. Accounts act for themselves and a contract
+// only for the user who authorized it as their wallet.
+fn acting_account(state: &State, sender: Address) -> Result<AccountAddress, ContractError> {
+    match sender {
+        Address::Account(acc) => Ok(acc),
+        Address::Contract(wallet) => state
+            .wallets
+            .get(&wallet)
+            .map(|account| *account)
+            .ok_or(ContractError::ContractSenderNotAllowed),
+    }
+}
+
+// Authorize a smart contract wallet (e.g. a multisig) to call set_limits and
+// self_exclude for the calling user, or remove it with None
 #[receive(
     contract = "safestake_registry",
-    name = "set_limits",
-    parameter = "SetLimitsParams",
+    name = "set_wallet",
+    parameter = "SetWalletParams",
     error = "ContractError",
     mutable
 )]
-fn set_limits(
+fn set_wallet(
     ctx: &ReceiveContext,
     host: &mut Host<State>,
 ) -> Result<(), ContractError> {
-    let params: SetLimitsParams = ctx.parameter_cursor().get()?;
-    
-    // Validate limits
-    if params.daily_limit > params.monthly_limit {
-        return Err(ContractError::InvalidLimits);
-    }
+    ensure_users_migrated(host.state())?;
+    let params: SetWalletParams = ctx.parameter_cursor().get()?;
     
-    let sender = ctx.sender();
-    let sender_hash = match sender {
-        Address::Account(acc) => hash_account(acc),
-        Address::Contract(_) => return Err(ContractError::ParseParams),
+    let sender_account = match ctx.sender() {
+        Address::Account(acc) => acc,
+        Address::Contract(_) => return Err(ContractError::ContractSenderNotAllowed),
     };
     
-    let current_time = ctx.metadata().slot_time();
+    if let Some(wallet) = params.wallet {
+        if host.state().wallets.get(&wallet).is_some_and(|owner| *owner != sender_account) {
+            return Err(ContractError::WalletInUse);
+        }
+    }
     
-    // Check if user exists
-    let user_exists = host.state().registry.get(&sender_hash).is_some();
+    let state = host.state_mut();
+    let mut user = state
+        .registry
+        .get_mut(&hash_account(sender_account))
+        .ok_or(ContractError::UserNotRegistered)?;
     
-    if !user_exists {
-        // Create new user (without age verification)
-        let new_user = UserCompliance {
-            identity_hash: sender_hash,
-            daily_limit: params.daily_limit,
-            monthly_limit: params.monthly_limit,
-            daily_spent: Amount::zero(),
-            monthly_spent: Amount::zero(),
-            last_reset_day: current_time,
-            last_reset_month: current_time,
-            cooldown_until: None,
-            platforms_used: host.state_builder().new_set(),
-            age_verified: false,  // NOT age-verified yet
+    if let Some(previous) = user.wallet {
+        state.wallets.remove(&previous);
+    }
+    if let Some(wallet) = params.wallet {
+        let _ = state.wallets.insert(wallet, sender_account);
+    }
+    user.wallet = params.wallet;
+    
+    Ok(())
+}
+
+// Set spending limits for the calling user
+#[receive(
+    contract = "safestake_registry",
+    name = "set_limits",
+    parameter = "SetLimitsParams",
+    error = "ContractError",
+    mutable
+)]
+fn set_limits(
+    ctx: &ReceiveContext,
+    host: &mut Host<State>,
+) -> Result<(), ContractError> {
+    ensure_users_migrated(host.state())?;
+    let params: SetLimitsParams = ctx.parameter_cursor().get()?;
+    let account = acting_account(host.state(), ctx.sender())?;
+    
+    update_limits(host, account, params, ctx.metadata().slot_time())
+}
+
+// Set a user's own limits, creating the user (without age verification) if needed
+fn update_limits(
+    host: &mut Host<State>,
+    account: AccountAddress,
+    params: SetLimitsParams,
+    current_time: Timestamp,
+) -> Result<(), ContractError> {
+    validate_limits(&params)?;
+    let user_hash = hash_account(account);
+    
+    // While limits are paused only existing users may lower their limits
+    if host.state().paused.limits {
+        let lowers_only = host
+            .state()
+            .registry
+            .get(&user_hash)
+            .is_some_and(|user| only_lowers_limits(&user, &params));
+        if !lowers_only {
+            return Err(ContractError::Paused);
+        }
+    }
+    
+    // Create the user if needed (without age verification)
+    if host.state().registry.get(&user_hash).is_none() {
+        let new_user =
+            UserCompliance::new(user_hash, false, current_time, host.state_builder());
+        let _ = host.state_mut().registry.insert(user_hash, new_user);
+    }
+    
+    let mut user = host.state_mut().registry.get_mut(&user_hash).unwrap();
+    apply_limits(&mut user, params);
+    
+    Ok(())
+}
+
+// Lower a user's limits on their behalf, e.g. when they ask customer support
+// (support staff only). Raising any limit is refused.
+#[receive(
+    contract = "safestake_registry",
+    name = "support_lower_limits",
+    parameter = "SupportLowerLimitsParams",
+    error = "ContractError",
+    mutable
+)]
+fn support_lower_limits(
+    ctx: &ReceiveContext,
+    host: &mut Host<State>,
+) -> Result<(), ContractError> {
+    ensure_users_migrated(host.state())?;
+    let params: SupportLowerLimitsParams = ctx.parameter_cursor().get()?;
+    validate_limits(&params.limits)?;
+    
+    if !has_role(host.state(), ctx.sender(), Role::Support) {
+        return Err(ContractError::Unauthorized);
+    }
+    
+    let mut user = host
+        .state_mut()
+        .registry
+        .get_mut(&hash_account(params.user_account))
+        .ok_or(ContractError::UserNotRegistered)?;
+    
+    if !only_lowers_limits(&user, &params.limits) {
+        return Err(ContractError::Unauthorized);
+    }
+    apply_limits(&mut user, params.limits);
+    
+    Ok(())
+}
+
+// Check that new limits are consistent with each other
+fn validate_limits(params: &SetLimitsParams) -> Result<(), ContractError> {
+    if params.daily_limit > params.monthly_limit {
+        return Err(ContractError::InvalidLimits);
+    }
+    
+    if let Some(max_stake) = params.max_stake {
+        if max_stake > params.daily_limit {
+            return Err(ContractError::InvalidLimits);
+        }
+    }
+    
+    if let (Some(per_hour), Some(per_day)) = (params.max_bets_per_hour, params.max_bets_per_day) {
+        if per_hour > per_day {
+            return Err(ContractError::InvalidLimits);
+        }
+    }
+    
+    Ok(())
+}
+
+// Replace the user's limits
+fn apply_limits(user: &mut UserCompliance, params: SetLimitsParams) {
+    user.daily_limit = params.daily_limit;
+    user.monthly_limit = params.monthly_limit;
+    user.max_stake = params.max_stake;
+    user.max_bets_per_hour = params.max_bets_per_hour;
+    user.max_bets_per_day = params.max_bets_per_day;
+    user.daily_time_limit = params.daily_time_limit;
+}
+
+// Whether new limits are each at most the user's current ones.
+// A limit that is unset counts as higher than any set limit.
+fn only_lowers_limits(user: &UserCompliance, params: &SetLimitsParams) -> bool {
+    fn no_higher<T: PartialOrd>(new: Option<T>, current: Option<T>) -> bool {
+        match (new, current) {
+            (_, None) => true,
+            (Some(new), Some(current)) => new <= current,
+            (None, Some(_)) => false,
+        }
+    }
+    
+    params.daily_limit <= user.daily_limit
+        && params.monthly_limit <= user.monthly_limit
+        && no_higher(params.max_stake, user.max_stake)
+        && no_higher(params.max_bets_per_hour, user.max_bets_per_hour)
+        && no_higher(params.max_bets_per_day, user.max_bets_per_day)
+        && no_higher(params.daily_time_limit, user.daily_time_limit)
+}
+
+// Set the calling user's spending limits on a single platform.
+// These apply in addition to the global daily and monthly limits.
+// Spending on a platform is only counted from when the user first sets limits
+// there, so that platforms the user does not limit are not named in state.
+// A new limit of 20/day after spending 50 there today still allows 20 more;
+// the global limits count the earlier spending. Clearing both limits removes
+// the platform's account, and its spending with it.
+#[receive(
+    contract = "safestake_registry",
+    name = "set_platform_limits",
+    parameter = "SetPlatformLimitsParams",
+    error = "ContractError",
+    mutable
+)]
+fn set_platform_limits(
+    ctx: &ReceiveContext,
+    host: &mut Host<State>,
+) -> Result<(), ContractError> {
+    ensure_users_migrated(host.state())?;
+    let params: SetPlatformLimitsParams = ctx.parameter_cursor().get()?;
+    
+    if let (Some(daily), Some(monthly)) = (params.daily_limit, params.monthly_limit) {
+        if daily > monthly {
+            return Err(ContractError::InvalidLimits);
+        }
+    }
+    
+    let sender_hash = match ctx.sender() {
+        Address::Account(acc) => hash_account(acc),
+        Address::Contract(_) => return Err(ContractError::ContractSenderNotAllowed),
+    };
+    
+    let mut user = host
+        .state_mut()
+        .registry
+        .get_mut(&sender_hash)
+        .ok_or(ContractError::UserNotRegistered)?;
+    
+    // Without limits the platform needs no entry, which would only record
+    // that the user gambles there
+    if params.daily_limit.is_none() && params.monthly_limit.is_none() {
+        let _ = user.platform_accounts.remove_and_get(&params.platform_id);
+        return Ok(());
+    }
+    
+    let existing = user.platform_accounts.get(&params.platform_id).map(|a| a.clone());
+    let account = account_with_limits(existing, &user, params.daily_limit, params.monthly_limit);
+    let _ = user.platform_accounts.insert(params.platform_id, account);
+    
+    Ok(())
+}
+
+// Set the calling user's spending limits on a single game category, counted
+// across all platforms. These apply in addition to the global and platform limits.
+#[receive(
+    contract = "safestake_registry",
+    name = "set_category_limits",
+    parameter = "SetCategoryLimitsParams",
+    error = "ContractError",
+    mutable
+)]
+fn set_category_limits(
+    ctx: &ReceiveContext,
+    host: &mut Host<State>,
+) -> Result<(), ContractError> {
+    ensure_users_migrated(host.state())?;
+    let params: SetCategoryLimitsParams = ctx.parameter_cursor().get()?;
+    
+    if let (Some(daily), Some(monthly)) = (params.daily_limit, params.monthly_limit) {
+        if daily > monthly {
+            return Err(ContractError::InvalidLimits);
+        }
+    }
+    
+    let sender_hash = match ctx.sender() {
+        Address::Account(acc) => hash_account(acc),
+        Address::Contract(_) => return Err(ContractError::ContractSenderNotAllowed),
+    };
+    
+    let mut user = host
+        .state_mut()
+        .registry
+        .get_mut(&sender_hash)
+        .ok_or(ContractError::UserNotRegistered)?;
+    
+    let existing = user.category_accounts.get(&params.category).map(|a| a.clone());
+    let account = account_with_limits(existing, &user, params.daily_limit, params.monthly_limit);
+    let _ = user.category_accounts.insert(params.category, account);
+    
+    Ok(())
+}
+
+// Platform or category account with new limits, keeping any spending already
+// recorded in it
+fn account_with_limits(
+    existing: Option<ScopedAccount>,
+    user: &UserCompliance,
+    daily_limit: Option<Amount>,
+    monthly_limit: Option<Amount>,
+) -> ScopedAccount {
+    match existing {
+        Some(existing) => ScopedAccount {
+            daily_limit,
+            monthly_limit,
+            ..existing
+        },
+        None => ScopedAccount {
+            daily_limit,
+            monthly_limit,
+            daily_spent: Amount::zero(),
+            monthly_spent: Amount::zero(),
+            day_started: user.last_reset_day,
+            month_started: user.last_reset_month,
+        },
+    }
+}
+
+// Set the contract-wide maximum stake on a single bet (admin only)
+#[receive(
+    contract = "safestake_registry",
+    name = "set_stake_cap",
+    parameter = "SetStakeCapParams",
+    error = "ContractError",
+    mutable
+)]
+fn set_stake_cap(
+    ctx: &ReceiveContext,
+    host: &mut Host<State>,
+) -> Result<(), ContractError> {
+    let params: SetStakeCapParams = ctx.parameter_cursor().get()?;
+    
+    if ctx.sender() != Address::Account(host.state().admin) {
+        return Err(ContractError::Unauthorized);
+    }
+    
+    host.state_mut().stake_cap = params.stake_cap;
+    Ok(())
+}
+
+// Halt or resume register_user, record_transaction and set_limits (admin only)
+#[receive(
+    contract = "safestake_registry",
+    name = "set_paused",
+    parameter = "PauseFlags",
+    error = "ContractError",
+    mutable
+)]
+fn set_paused(
+    ctx: &ReceiveContext,
+    host: &mut Host<State>,
+) -> Result<(), ContractError> {
+    let params: PauseFlags = ctx.parameter_cursor().get()?;
+    
+    if ctx.sender() != Address::Account(host.state().admin) {
+        return Err(ContractError::Unauthorized);
+    }
+    
+    host.state_mut().paused = params;
+    Ok(())
+}
+
+// View which entrypoints are currently halted
+#[receive(
+    contract = "safestake_registry",
+    name = "get_paused",
+    return_value = "PauseFlags",
+    error = "ContractError"
+)]
+fn get_paused(_ctx: &ReceiveContext, host: &Host<State>) -> Result<PauseFlags, ContractError> {
+    Ok(host.state().paused)
+}
+
+// Set the spend in the last 30 days above which bets need an affordability
+// attestation (admin only)
+#[receive(
+    contract = "safestake_registry",
+    name = "set_affordability_threshold",
+    parameter = "SetAffordabilityThresholdParams",
+    error = "ContractError",
+    mutable
+)]
+fn set_affordability_threshold(
+    ctx: &ReceiveContext,
+    host: &mut Host<State>,
+) -> Result<(), ContractError> {
+    let params: SetAffordabilityThresholdParams = ctx.parameter_cursor().get()?;
+    
+    if ctx.sender() != Address::Account(host.state().admin) {
+        return Err(ContractError::Unauthorized);
+    }
+    
+    host.state_mut().affordability_threshold = params.threshold;
+    Ok(())
+}
+
+// Upgrade the contract to a new module, keeping its state (admin only).
+// Uses the low-level host so the old State is not written back over whatever
+// migrate stores after the upgrade. Refused while users from the previous
+// upgrade are still waiting for migrate_users.
+#[receive(
+    contract = "safestake_registry",
+    name = "upgrade",
+    parameter = "UpgradeParams",
+    error = "ContractError",
+    low_level
+)]
+fn upgrade(ctx: &ReceiveContext, host: &mut LowLevelHost) -> Result<(), ContractError> {
+    let admin = stored_admin(host.state())?;
+    
+    if ctx.sender() != Address::Account(admin) {
+        return Err(ContractError::Unauthorized);
+    }
+    if stored_version(host.state())? == STATE_VERSION {
+        let state: State = host.state().read_root()?;
+        ensure_users_migrated(&state)?;
+    }
+    
+    let params: UpgradeParams = ctx.parameter_cursor().get()?;
+    host.upgrade(params.module)?;
+    
+    if let Some((entrypoint, parameter)) = params.migrate {
+        host.invoke_contract_raw(
+            &ctx.self_address(),
+            parameter.as_parameter(),
+            entrypoint.as_entrypoint_name(),
+            Amount::zero(),
+        )?;
+    }
+    Ok(())
+}
+
+// Read the next field of a stored value, whichever layout it was stored in
+fn read_field<T: DeserialWithState<StateApi>>(
+    state_api: &StateApi,
+    source: &mut impl Read,
+) -> Result<T, ContractError> {
+    Ok(T::deserial_with_state(state_api, source)?)
+}
+
+// Layout version of the stored state
+fn stored_version(state_api: &StateApi) -> Result<u32, ContractError> {
+    let mut root = state_api
+        .lookup_entry(&[])
+        .ok_or(ContractError::UnsupportedStateVersion)?;
+    read_field(state_api, &mut root)
+}
+
+// Fail while users from before an upgrade are still in their old layout.
+// Entrypoints that change a user call this first, so nothing is done to a user
+// that is not back in the registry yet.
+fn ensure_users_migrated(state: &State) -> Result<(), ContractError> {
+    if state.pending_users.is_some() {
+        return Err(ContractError::MigrationPending);
+    }
+    Ok(())
+}
+
+// Admin of the stored state. Only the leading fields are read, which every
+// layout stores the same way, so the admin can still upgrade from state that
+// was never migrated.
+fn stored_admin(state_api: &StateApi) -> Result<AccountAddress, ContractError> {
+    let mut root = state_api
+        .lookup_entry(&[])
+        .ok_or(ContractError::UnsupportedStateVersion)?;
+    let _version: u32 = read_field(state_api, &mut root)?;
+    let _registry: StateMap<IdentityHash, UserCompliance, StateApi> =
+        read_field(state_api, &mut root)?;
+    let _excluded_users: StateSet<IdentityHash, StateApi> = read_field(state_api, &mut root)?;
+    let _verifier_key: PublicKeyEd25519 = read_field(state_api, &mut root)?;
+    read_field(state_api, &mut root)
+}
+
+// Roles for the regulators of a layout before version 3. The old set is deleted.
+fn regulator_roles(
+    regulators: StateSet<AccountAddress, StateApi>,
+    state_builder: &mut StateBuilder,
+) -> StateSet<(AccountAddress, Role), StateApi> {
+    let mut roles = state_builder.new_set();
+    for regulator in regulators.iter() {
+        roles.insert((*regulator, Role::Regulator));
+    }
+    regulators.delete();
+    roles
+}
+
+// Platform history of a layout before version 9, which an observer could
+// open: platform IDs in the clear before version 7, then commitments under a
+// salt kept in state. The sets are deleted; only the count is kept.
+fn discard_platform_history(
+    state_api: &StateApi,
+    source: &mut impl Read,
+    version: u32,
+) -> Result<u32, ContractError> {
+    if version < 7 {
+        let platforms: StateSet<String, StateApi> = read_field(state_api, source)?;
+        let count = platforms.iter().count() as u32;
+        platforms.delete();
+        return Ok(count);
+    }
+    let _salt: [u8; 32] = read_field(state_api, source)?;
+    let platforms: StateSet<[u8; 32], StateApi> = read_field(state_api, source)?;
+    platforms.delete();
+    let count = read_field(state_api, source)?;
+    if version >= 8 {
+        let platforms_today: StateSet<[u8; 32], StateApi> = read_field(state_api, source)?;
+        platforms_today.delete();
+    }
+    Ok(count)
+}
+
+// Remove the platform accounts of a layout before version 9 that carry no
+// limits. Those layouts made one for every platform the user bet on.
+fn drop_unlimited_platform_accounts(user: &mut UserCompliance) {
+    let unlimited: Vec<String> = user
+        .platform_accounts
+        .iter()
+        .filter(|(_, account)| account.daily_limit.is_none() && account.monthly_limit.is_none())
+        .map(|(platform_id, _)| platform_id.clone())
+        .collect();
+    for platform_id in unlimited {
+        user.platform_accounts.remove(&platform_id);
+    }
+}
+
+// Read a user stored in the given layout version and convert it to the
+// current one. Fields are read in the order that layout stored them in;
+// fields it did not have yet get the value a new user starts with.
+fn migrate_user(
+    state_api: &StateApi,
+    state_builder: &mut StateBuilder,
+    source: &mut impl Read,
+    version: u32,
+    current_time: Timestamp,
+) -> Result<UserCompliance, ContractError> {
+    let mut platforms_used_count = 0;
+    let mut user = UserCompliance {
+        identity_hash: read_field(state_api, source)?,
+        daily_limit: read_field(state_api, source)?,
+        monthly_limit: read_field(state_api, source)?,
+        daily_spent: read_field(state_api, source)?,
+        monthly_spent: read_field(state_api, source)?,
+        last_reset_day: read_field(state_api, source)?,
+        last_reset_month: read_field(state_api, source)?,
+        max_stake: read_field(state_api, source)?,
+        max_bets_per_hour: read_field(state_api, source)?,
+        max_bets_per_day: read_field(state_api, source)?,
+        hourly_bets: read_field(state_api, source)?,
+        daily_bets: read_field(state_api, source)?,
+        last_reset_hour: read_field(state_api, source)?,
+        daily_time_limit: read_field(state_api, source)?,
+        daily_play_time: read_field(state_api, source)?,
+        active_sessions: read_field(state_api, source)?,
+        platform_accounts: read_field(state_api, source)?,
+        platform_exclusions: read_field(state_api, source)?,
+        category_accounts: read_field(state_api, source)?,
+        category_exclusions: read_field(state_api, source)?,
+        cooldown_until: read_field(state_api, source)?,
+        exclusion_source: read_field(state_api, source)?,
+        // Before version 6 an ended self-exclusion lifted straight away
+        reactivation_required: if version >= 6 {
+            read_field(state_api, source)?
+        } else {
+            false
+        },
+        reactivation_requested_at: if version >= 6 {
+            read_field(state_api, source)?
+        } else {
+            None
+        },
+        trusted_contacts: read_field(state_api, source)?,
+        wallet: if version >= 4 {
+            read_field(state_api, source)?
+        } else {
+            None
+        },
+        auto_cooldown: read_field(state_api, source)?,
+        rejected_attempts: read_field(state_api, source)?,
+        rejection_window_start: read_field(state_api, source)?,
+        hourly_rejections: read_field(state_api, source)?,
+        daily_rejections: read_field(state_api, source)?,
+        last_rejection: read_field(state_api, source)?,
+        risk: read_field(state_api, source)?,
+        affordability_attested_until: read_field(state_api, source)?,
+        affordability_nonce: if version >= 10 {
+            read_field(state_api, source)?
+        } else {
+            0
+        },
+        spent_by_day: if version >= 10 {
+            read_field(state_api, source)?
+        } else {
+            state_builder.new_map()
+        },
+        // Older platform history cannot be recommitted without the user's salt,
+        // so only its count carries over and later bets commit afresh
+        platforms_used: if version >= 9 {
+            read_field(state_api, source)?
+        } else {
+            platforms_used_count = discard_platform_history(state_api, source, version)?;
+            state_builder.new_set()
+        },
+        platforms_used_count: if version >= 9 {
+            read_field(state_api, source)?
+        } else {
+            platforms_used_count
+        },
+        platforms_bet_today: if version >= 9 {
+            read_field(state_api, source)?
+        } else {
+            state_builder.new_set()
+        },
+        age_verified: read_field(state_api, source)?,
+    };
+    if version < 9 {
+        drop_unlimited_platform_accounts(&mut user);
+    }
+    // Older layouts kept no days, so the spending of the current monthly
+    // period counts as spent today and stays in the window for its full length
+    if version < 10 {
+        let (_, monthly_spent) = current_spending(&user, current_time);
+        if monthly_spent > Amount::zero() {
+            add_daily_spending(&mut user, monthly_spent, current_time);
+        }
+    }
+    Ok(user)
+}
+
+// Convert the stored state to this module's layout. Only the contract itself
+// may call it, as part of upgrade. Calling it on up-to-date state does nothing.
+// Every layout since version 1 can be converted. Modules from before the
+// version tag had no upgrade entrypoint, so their untagged state can never
+// reach this.
+// Only the root is converted here, so the upgrade costs the same however many
+// users there are. The users stay under the old registry's prefix until
+// migrate_users converts them.
+#[receive(
+    contract = "safestake_registry",
+    name = "migrate",
+    error = "ContractError",
+    low_level
+)]
+fn migrate(ctx: &ReceiveContext, host: &mut LowLevelHost) -> Result<(), ContractError> {
+    if ctx.sender() != Address::Contract(ctx.self_address()) {
+        return Err(ContractError::Unauthorized);
+    }
+    
+    let mut state_api = host.state().clone();
+    let mut root = state_api
+        .lookup_entry(&[])
+        .ok_or(ContractError::UnsupportedStateVersion)?;
+    let version: u32 = read_field(&state_api, &mut root)?;
+    if version == STATE_VERSION {
+        return Ok(());
+    }
+    if version == 0 || version > STATE_VERSION {
+        return Err(ContractError::UnsupportedStateVersion);
+    }
+    
+    // Fields are read in the order the old layout stored them in
+    let state_builder = host.state_builder();
+    let old_registry: StateMap<IdentityHash, UserCompliance, StateApi> =
+        read_field(&state_api, &mut root)?;
+    let mut prefix = [0u8; 8];
+    prefix.copy_from_slice(&to_bytes(&old_registry));
+    let state = State {
+        version: STATE_VERSION,
+        registry: state_builder.new_map(),
+        excluded_users: read_field(&state_api, &mut root)?,
+        verifier_key: read_field(&state_api, &mut root)?,
+        admin: read_field(&state_api, &mut root)?,
+        stake_cap: read_field(&state_api, &mut root)?,
+        operators: read_field(&state_api, &mut root)?,
+        wallets: if version >= 4 {
+            read_field(&state_api, &mut root)?
+        } else {
+            state_builder.new_map()
+        },
+        permit_nonces: if version >= 5 {
+            read_field(&state_api, &mut root)?
+        } else {
+            state_builder.new_map()
+        },
+        roles: if version >= 3 {
+            read_field(&state_api, &mut root)?
+        } else {
+            regulator_roles(read_field(&state_api, &mut root)?, state_builder)
+        },
+        risk_review_threshold: read_field(&state_api, &mut root)?,
+        affordability_threshold: read_field(&state_api, &mut root)?,
+        paused: if version >= 2 {
+            read_field(&state_api, &mut root)?
+        } else {
+            PauseFlags::default()
+        },
+        pending_users: Some(PendingUsers { version, prefix }),
+    };
+    drop(root);
+    
+    state_api.write_root(&state);
+    Ok(())
+}
+
+// Keys of up to max_users users still under the old registry's prefix. Each
+// user is stored in an entry of its own, keyed by the prefix and the identity
+// hash.
+fn pending_user_keys(state_api: &mut StateApi, prefix: &[u8], max_users: u32) -> Vec<Vec<u8>> {
+    // The host reports a prefix with no entries left as not found
+    let mut users = match state_api.iterator(prefix) {
+        Ok(users) => users,
+        Err(_) => return Vec::new(),
+    };
+    let keys = users
+        .by_ref()
+        .take(max_users as usize)
+        .map(|entry| entry.get_key().to_vec())
+        .collect();
+    state_api.delete_iterator(users);
+    keys
+}
+
+// Convert up to max_users users left in their old layout by an upgrade, and
+// return whether any are still waiting. Anyone may call it, and should keep
+// calling it until it returns false. Until then users not converted yet read
+// as unregistered, and entrypoints that change users fail with
+// MigrationPending.
+#[receive(
+    contract = "safestake_registry",
+    name = "migrate_users",
+    parameter = "u32",
+    return_value = "bool",
+    error = "ContractError",
+    low_level
+)]
+fn migrate_users(ctx: &ReceiveContext, host: &mut LowLevelHost) -> Result<bool, ContractError> {
+    let max_users: u32 = ctx.parameter_cursor().get()?;
+    
+    if max_users > MAX_MIGRATION_BATCH {
+        return Err(ContractError::BatchTooLarge);
+    }
+    
+    let mut state_api = host.state().clone();
+    let mut state: State = state_api.read_root()?;
+    let pending = match state.pending_users {
+        Some(pending) => pending,
+        None => return Ok(false),
+    };
+    
+    let keys = pending_user_keys(&mut state_api, &pending.prefix, max_users);
+    let current_time = ctx.metadata().slot_time();
+    for key in keys {
+        let identity_hash: IdentityHash = from_bytes(&key[pending.prefix.len()..])?;
+        let mut entry = state_api.lookup_entry(&key).unwrap_abort();
+        let user = migrate_user(
+            &state_api,
+            host.state_builder(),
+            &mut entry,
+            pending.version,
+            current_time,
+        )?;
+        state_api.delete_entry(entry).unwrap_abort();
+        let _ = state.registry.insert(identity_hash, user);
+    }
+    
+    let remaining = !pending_user_keys(&mut state_api, &pending.prefix, 1).is_empty();
+    if !remaining {
+        state.pending_users = None;
+    }
+    
+    state_api.write_root(&state);
+    Ok(remaining)
+}
+
+// Get the layout version of the stored state
+#[receive(
+    contract = "safestake_registry",
+    name = "get_state_version",
+    return_value = "u32",
+    error = "ContractError"
+)]
+fn get_state_version(_ctx: &ReceiveContext, host: &Host<State>) -> Result<u32, ContractError> {
+    Ok(host.state().version)
+}
+
+// Store an affordability attestation for a user. The backend verifier signs it
+// after an enhanced check off-chain; anyone may submit the signed attestation.
+// The signed message is ATTESTATION_DOMAIN, this contract's address, the
+// account, the nonce and valid_until (both little-endian u64, valid_until in
+// milliseconds), so it cannot be replayed on another instance. The nonce must
+// be the user's next one, so an older attestation cannot be submitted again
+// to replace a newer one.
+#[receive(
+    contract = "safestake_registry",
+    name = "submit_affordability_attestation",
+    parameter = "AffordabilityAttestationParams",
+    error = "ContractError",
+    crypto_primitives,
+    mutable
+)]
+fn submit_affordability_attestation(
+    ctx: &ReceiveContext,
+    host: &mut Host<State>,
+    crypto_primitives: &impl HasCryptoPrimitives,
+) -> Result<(), ContractError> {
+    ensure_users_migrated(host.state())?;
+    let params: AffordabilityAttestationParams = ctx.parameter_cursor().get()?;
+    
+    let mut message = ATTESTATION_DOMAIN.to_vec();
+    message.extend_from_slice(&to_bytes(&ctx.self_address()));
+    message.extend_from_slice(params.account.as_ref());
+    message.extend_from_slice(&params.nonce.to_le_bytes());
+    message.extend_from_slice(&params.valid_until.timestamp_millis().to_le_bytes());
+    
+    let is_valid = crypto_primitives.verify_ed25519_signature(
+        host.state().verifier_key,
+        params.signature,
+        &message,
+    );
+    if !is_valid {
+        return Err(ContractError::InvalidSignature);
+    }
+    
+    if params.valid_until <= ctx.metadata().slot_time() {
+        return Err(ContractError::AttestationExpired);
+    }
+    
+    let mut user = host
+        .state_mut()
+        .registry
+        .get_mut(&hash_account(params.account))
+        .ok_or(ContractError::UserNotRegistered)?;
+    if params.nonce != user.affordability_nonce {
+        return Err(ContractError::NonceMismatch);
+    }
+    user.affordability_nonce += 1;
+    user.affordability_attested_until = Some(params.valid_until);
+    
+    Ok(())
+}
+
+// Get the nonce the user's next affordability attestation must use
+#[receive(
+    contract = "safestake_registry",
+    name = "get_attestation_nonce",
+    parameter = "AccountAddress",
+    return_value = "u64",
+    error = "ContractError"
+)]
+fn get_attestation_nonce(ctx: &ReceiveContext, host: &Host<State>) -> Result<u64, ContractError> {
+    let account: AccountAddress = ctx.parameter_cursor().get()?;
+    let user = host
+        .state()
+        .registry
+        .get(&hash_account(account))
+        .ok_or(ContractError::UserNotRegistered)?;
+    Ok(user.affordability_nonce)
+}
+
+// Amount the user may still spend without an affordability attestation, or
+// None if they are not held back by the affordability threshold. The threshold
+// applies to the spend over the last AFFORDABILITY_WINDOW_DAYS days.
+fn affordability_headroom(
+    user: &UserCompliance,
+    affordability_threshold: Option<Amount>,
+    current_time: Timestamp,
+) -> Option<Amount> {
+    let threshold = affordability_threshold?;
+    let attested = user
+        .affordability_attested_until
+        .is_some_and(|valid_until| current_time < valid_until);
+    if attested {
+        return None;
+    }
+    Some(remaining_under(threshold, recent_spending(user, current_time)))
+}
+
+// Number of the UTC day a time falls on, counted from the epoch
+fn day_number(time: Timestamp) -> u64 {
+    time.timestamp_millis() / (24 * 60 * 60 * 1000)
+}
+
+// Spending over the last AFFORDABILITY_WINDOW_DAYS days, today included
+fn recent_spending(user: &UserCompliance, current_time: Timestamp) -> Amount {
+    let today = day_number(current_time);
+    let micro_ccd = user
+        .spent_by_day
+        .iter()
+        .filter(|(day, _)| today.saturating_sub(**day) < AFFORDABILITY_WINDOW_DAYS)
+        .fold(0u64, |total, (_, spent)| total.saturating_add(spent.micro_ccd));
+    Amount::from_micro_ccd(micro_ccd)
+}
+
+// Add a bet to today's spending and forget the days that have left the window,
+// so at most AFFORDABILITY_WINDOW_DAYS days are kept
+fn add_daily_spending(user: &mut UserCompliance, amount: Amount, current_time: Timestamp) {
+    let today = day_number(current_time);
+    let expired: Vec<u64> = user
+        .spent_by_day
+        .iter()
+        .map(|(day, _)| *day)
+        .filter(|day| today.saturating_sub(*day) >= AFFORDABILITY_WINDOW_DAYS)
+        .collect();
+    for day in expired {
+        user.spent_by_day.remove(&day);
+    }
+    let spent = user.spent_by_day.get(&today).map(|spent| *spent).unwrap_or_else(Amount::zero);
+    let _ = user
+        .spent_by_day
+        .insert(today, Amount::from_micro_ccd(spent.micro_ccd.saturating_add(amount.micro_ccd)));
+}
+
+// Set the risk score at which check_eligibility returns ReviewRequired (admin only)
+#[receive(
+    contract = "safestake_registry",
+    name = "set_risk_review_threshold",
+    parameter = "SetRiskThresholdParams",
+    error = "ContractError",
+    mutable
+)]
+fn set_risk_review_threshold(
+    ctx: &ReceiveContext,
+    host: &mut Host<State>,
+) -> Result<(), ContractError> {
+    let params: SetRiskThresholdParams = ctx.parameter_cursor().get()?;
+    
+    if ctx.sender() != Address::Account(host.state().admin) {
+        return Err(ContractError::Unauthorized);
+    }
+    
+    if params.threshold.is_some_and(|threshold| threshold > 100) {
+        return Err(ContractError::InvalidLimits);
+    }
+    
+    host.state_mut().risk_review_threshold = params.threshold;
+    Ok(())
+}
+
+// Register an operator account for its platform, or remove it (operator managers only).
+// Registered operators may exclude users from their own platform.
+#[receive(
+    contract = "safestake_registry",
+    name = "set_operator",
+    parameter = "SetOperatorParams",
+    error = "ContractError",
+    mutable
+)]
+fn set_operator(
+    ctx: &ReceiveContext,
+    host: &mut Host<State>,
+) -> Result<(), ContractError> {
+    let params: SetOperatorParams = ctx.parameter_cursor().get()?;
+    
+    if !has_role(host.state(), ctx.sender(), Role::OperatorManager) {
+        return Err(ContractError::Unauthorized);
+    }
+    
+    match params.platform_id {
+        Some(platform_id) => {
+            let _ = host.state_mut().operators.insert(params.operator, platform_id);
+        }
+        None => host.state_mut().operators.remove(&params.operator),
+    }
+    Ok(())
+}
+
+// Platform the sender is the registered operator of
+fn operator_platform(state: &State, sender: Address) -> Result<String, ContractError> {
+    match sender {
+        Address::Account(account) => state
+            .operators
+            .get(&account)
+            .map(|platform_id| platform_id.clone())
+            .ok_or(ContractError::Unauthorized),
+        Address::Contract(_) => Err(ContractError::Unauthorized),
+    }
+}
+
+// Whether the sender is the registered operator of the platform
+fn is_platform_operator(state: &State, sender: Address, platform_id: &str) -> bool {
+    match sender {
+        Address::Account(account) => state
+            .operators
+            .get(&account)
+            .is_some_and(|operator_platform| operator_platform.as_str() == platform_id),
+        Address::Contract(_) => false,
+    }
+}
+
+// Whether the sender is the admin or an account granted the role
+fn has_role(state: &State, sender: Address, role: Role) -> bool {
+    match sender {
+        Address::Account(account) => {
+            account == state.admin || state.roles.contains(&(account, role))
+        }
+        Address::Contract(_) => false,
+    }
+}
+
+// Give an account a role (admin only)
+#[receive(
+    contract = "safestake_registry",
+    name = "grant_role",
+    parameter = "RoleParams",
+    error = "ContractError",
+    mutable,
+    enable_logger
+)]
+fn grant_role(
+    ctx: &ReceiveContext,
+    host: &mut Host<State>,
+    logger: &mut Logger,
+) -> Result<(), ContractError> {
+    let params: RoleParams = ctx.parameter_cursor().get()?;
+    
+    if ctx.sender() != Address::Account(host.state().admin) {
+        return Err(ContractError::Unauthorized);
+    }
+    
+    if host.state_mut().roles.insert((params.account, params.role)) {
+        logger.log(&Event::RoleGranted(params))?;
+    }
+    Ok(())
+}
+
+// Take a role away from an account (admin only)
+#[receive(
+    contract = "safestake_registry",
+    name = "revoke_role",
+    parameter = "RoleParams",
+    error = "ContractError",
+    mutable,
+    enable_logger
+)]
+fn revoke_role(
+    ctx: &ReceiveContext,
+    host: &mut Host<State>,
+    logger: &mut Logger,
+) -> Result<(), ContractError> {
+    let params: RoleParams = ctx.parameter_cursor().get()?;
+    
+    if ctx.sender() != Address::Account(host.state().admin) {
+        return Err(ContractError::Unauthorized);
+    }
+    
+    if host.state_mut().roles.remove(&(params.account, params.role)) {
+        logger.log(&Event::RoleRevoked(params))?;
+    }
+    Ok(())
+}
+
+// Replace the backend verifier's public key, e.g. after a key compromise
+// (verifier key managers only)
+#[receive(
+    contract = "safestake_registry",
+    name = "set_verifier_key",
+    parameter = "SetVerifierKeyParams",
+    error = "ContractError",
+    mutable
+)]
+fn set_verifier_key(
+    ctx: &ReceiveContext,
+    host: &mut Host<State>,
+) -> Result<(), ContractError> {
+    let params: SetVerifierKeyParams = ctx.parameter_cursor().get()?;
+    
+    if !has_role(host.state(), ctx.sender(), Role::VerifierKeyManager) {
+        return Err(ContractError::Unauthorized);
+    }
+    
+    host.state_mut().verifier_key = params.verifier_key;
+    Ok(())
+}
+
+// Self-exclude from all gambling platforms for a specified duration
+#[receive(
+    contract = "safestake_registry",
+    name = "self_exclude",
+    parameter = "SelfExcludeParams",
+    error = "ContractError",
+    mutable,
+    enable_logger
+)]
+fn self_exclude(
+    ctx: &ReceiveContext,
+    host: &mut Host<State>,
+    logger: &mut Logger,
+) -> Result<(), ContractError> {
+    ensure_users_migrated(host.state())?;
+    let params: SelfExcludeParams = ctx.parameter_cursor().get()?;
+    let account = acting_account(host.state(), ctx.sender())?;
+    
+    exclude_self(host.state_mut(), logger, account, params, ctx.metadata().slot_time())
+}
+
+// Exclude a user from all platforms at their own request
+fn exclude_self(
+    state: &mut State,
+    logger: &mut Logger,
+    sender_account: AccountAddress,
+    params: SelfExcludeParams,
+    current_time: Timestamp,
+) -> Result<(), ContractError> {
+    let sender_hash = hash_account(sender_account);
+    let was_excluded = state.excluded_users.contains(&sender_hash);
+    
+    state.excluded_users.insert(sender_hash);
+    
+    let duration_millis = params.duration_days as u64 * 24 * 60 * 60 * 1000;
+    let cooldown_until = current_time.checked_add(Duration::from_millis(duration_millis))
+        .ok_or(ContractError::ParseParams)?;
+    
+    let mut until = Some(cooldown_until);
+    let mut source = ExclusionSource {
+        issuer: ExclusionIssuer::User,
+        reason: ExclusionReason::UserRequest,
+    };
+    if let Some(mut user) = state.registry.get_mut(&sender_hash) {
+        (until, source) = extend_exclusion(&mut user, was_excluded, until, source, current_time);
+        user.reactivation_required = true;
+        user.reactivation_requested_at = None;
+    }
+    
+    logger.log(&Event::ExclusionImposed(ExclusionEvent {
+        user_account: sender_account,
+        scope: ExclusionScope::Global,
+        until,
+        source,
+    }))?;
+    
+    Ok(())
+}
+
+// End of the wait after the user requested reactivation, if they have
+fn reactivation_time(user: &UserCompliance) -> Option<Timestamp> {
+    user.reactivation_requested_at
+        .and_then(|requested_at| requested_at.checked_add(Duration::from_hours(REACTIVATION_WAIT_HOURS)))
+}
+
+// Whether the user's self-exclusion needs reactivation that is not complete yet
+fn awaiting_reactivation(user: &UserCompliance, current_time: Timestamp) -> bool {
+    user.reactivation_required
+        && match reactivation_time(user) {
+            Some(reactivates_at) => current_time < reactivates_at,
+            None => true,
+        }
+}
+
+// Ask to return after a self-exclusion has ended. Betting resumes
+// REACTIVATION_WAIT_HOURS after the request; asking again does not restart
+// the wait.
+#[receive(
+    contract = "safestake_registry",
+    name = "request_reactivation",
+    error = "ContractError",
+    mutable,
+    enable_logger
+)]
+fn request_reactivation(
+    ctx: &ReceiveContext,
+    host: &mut Host<State>,
+    logger: &mut Logger,
+) -> Result<(), ContractError> {
+    ensure_users_migrated(host.state())?;
+    let account = acting_account(host.state(), ctx.sender())?;
+    let current_time = ctx.metadata().slot_time();
+    let user_hash = hash_account(account);
+    let is_excluded = host.state().excluded_users.contains(&user_hash);
+    
+    let mut user = host
+        .state_mut()
+        .registry
+        .get_mut(&user_hash)
+        .ok_or(ContractError::UserNotRegistered)?;
+    
+    if !user.reactivation_required {
+        return Err(ContractError::ReactivationNotRequired);
+    }
+    
+    // The wait only starts once the exclusion is over
+    if is_excluded {
+        match user.cooldown_until {
+            Some(until) if current_time < until => return Err(ContractError::OnCooldown),
+            None => return Err(ContractError::SelfExcluded),
+            Some(_) => {}
+        }
+    }
+    
+    if user.reactivation_requested_at.is_some() {
+        return Ok(());
+    }
+    user.reactivation_requested_at = Some(current_time);
+    let reactivates_at = reactivation_time(&user).ok_or(ContractError::ParseParams)?;
+    drop(user);
+    
+    logger.log(&Event::ReactivationRequested(ReactivationEvent {
+        user_account: account,
+        reactivates_at,
+    }))?;
+    
+    Ok(())
+}
+
+// Whether the signatures meet the account's key and credential thresholds
+fn verify_account_signature(
+    keys: &AccountPublicKeys,
+    signatures: &AccountSignatures,
+    message: &[u8],
+    crypto_primitives: &impl HasCryptoPrimitives,
+) -> bool {
+    let valid_credentials = keys
+        .keys
+        .iter()
+        .filter(|(credential_index, credential)| {
+            let Some(credential_signatures) = signatures.sigs.get(credential_index) else {
+                return false;
+            };
+            let valid_keys = credential
+                .keys
+                .iter()
+                .filter(|(key_index, key)| match (key, credential_signatures.sigs.get(key_index)) {
+                    (PublicKey::Ed25519(key), Some(Signature::Ed25519(signature))) => {
+                        crypto_primitives.verify_ed25519_signature(*key, *signature, message)
+                    }
+                    _ => false,
+                })
+                .count();
+            valid_keys >= u8::from(credential.threshold) as usize
+        })
+        .count();
+    valid_credentials >= u8::from(keys.threshold) as usize
+}
+
+// Carry out a set_limits or self_exclude signed by the user, so an operator
+// or backend can submit it and pay the energy. The user signs the SHA-256
+// hash of their account address, 8 zero bytes and the serialized message,
+// the same format wallets use for signing messages.
+#[receive(
+    contract = "safestake_registry",
+    name = "permit",
+    parameter = "PermitParams",
+    error = "ContractError",
+    crypto_primitives,
+    mutable,
+    enable_logger
+)]
+fn permit(
+    ctx: &ReceiveContext,
+    host: &mut Host<State>,
+    logger: &mut Logger,
+    crypto_primitives: &impl HasCryptoPrimitives,
+) -> Result<(), ContractError> {
+    ensure_users_migrated(host.state())?;
+    let params: PermitParams = ctx.parameter_cursor().get()?;
+    let current_time = ctx.metadata().slot_time();
+    let message = params.message;
+    
+    if message.contract_address != ctx.self_address() {
+        return Err(ContractError::InvalidSignature);
+    }
+    if message.expiry <= current_time {
+        return Err(ContractError::PermitExpired);
+    }
+    
+    let nonce = host.state().permit_nonces.get(&params.signer).map(|n| *n).unwrap_or(0);
+    if message.nonce != nonce {
+        return Err(ContractError::NonceMismatch);
+    }
+    
+    let mut signed_data = params.signer.0.to_vec();
+    signed_data.extend_from_slice(&[0u8; 8]);
+    signed_data.extend_from_slice(&to_bytes(&message));
+    let message_hash = crypto_primitives.hash_sha2_256(&signed_data);
+    
+    let keys = host
+        .account_public_keys(params.signer)
+        .map_err(|_| ContractError::InvalidSignature)?;
+    if !verify_account_signature(&keys, &params.signature, &message_hash.0, crypto_primitives) {
+        return Err(ContractError::InvalidSignature);
+    }
+    
+    let _ = host.state_mut().permit_nonces.insert(params.signer, nonce + 1);
+    
+    match message.action {
+        PermitAction::SetLimits(limits) => {
+            update_limits(host, params.signer, limits, current_time)
+        }
+        PermitAction::SelfExclude(exclusion) => {
+            exclude_self(host.state_mut(), logger, params.signer, exclusion, current_time)
+        }
+    }
+}
+
+// Get the nonce the account's next permit message must use
+#[receive(
+    contract = "safestake_registry",
+    name = "get_permit_nonce",
+    parameter = "AccountAddress",
+    return_value = "u64",
+    error = "ContractError"
+)]
+fn get_permit_nonce(ctx: &ReceiveContext, host: &Host<State>) -> Result<u64, ContractError> {
+    let account: AccountAddress = ctx.parameter_cursor().get()?;
+    Ok(host.state().permit_nonces.get(&account).map(|n| *n).unwrap_or(0))
+}
+
+// Reset the hourly, daily and monthly counters whose period has elapsed
+fn reset_elapsed_periods(user: &mut UserCompliance, current_time: Timestamp) {
+    // Check if hourly reset is needed
+    let time_since_hourly = current_time.duration_since(user.last_reset_hour);
+    if let Some(duration) = time_since_hourly {
+        if duration.hours() >= 1 {
+            user.hourly_bets = 0;
+            user.hourly_rejections = 0;
+            user.last_reset_hour = current_time;
+        }
+    }
+    
+    // Check if daily reset is needed
+    let time_since_daily = current_time.duration_since(user.last_reset_day);
+    if let Some(duration) = time_since_daily {
+        if duration.days() >= 1 {
+            // Fold the finished day into the average as a 7-day moving average
+            let average = user.risk.average_daily_spend.micro_ccd;
+            user.risk.average_daily_spend = Amount::from_micro_ccd(
+                average.saturating_mul(6).saturating_add(user.daily_spent.micro_ccd) / 7,
+            );
+            user.risk.platforms_today = 0;
+            user.platforms_bet_today.clear();
+            user.daily_spent = Amount::zero();
+            user.daily_bets = 0;
+            user.daily_rejections = 0;
+            user.daily_play_time = Duration::from_millis(0);
+            user.last_reset_day = current_time;
+        }
+    }
+    
+    // Check if monthly reset is needed
+    let time_since_monthly = current_time.duration_since(user.last_reset_month);
+    if let Some(duration) = time_since_monthly {
+        if duration.days() >= 30 {
+            user.monthly_spent = Amount::zero();
+            user.risk.late_night_bets = 0;
+            user.last_reset_month = current_time;
+        }
+    }
+}
+
+// Whether one more bet would go over the user's hourly or daily bet count
+fn bet_count_reached(user: &UserCompliance, hourly_bets: u32, daily_bets: u32) -> bool {
+    let hourly_reached = user.max_bets_per_hour.is_some_and(|max| hourly_bets >= max);
+    let daily_reached = user.max_bets_per_day.is_some_and(|max| daily_bets >= max);
+    hourly_reached || daily_reached
+}
+
+// Play time today across all platforms: ended sessions plus the part of every
+// open session that falls after the last daily reset
+fn current_play_time(user: &UserCompliance, current_time: Timestamp) -> Duration {
+    // A day has passed since the last reset, so the next action starts a new day
+    let time_since_daily = current_time.duration_since(user.last_reset_day);
+    if time_since_daily.is_some_and(|duration| duration.days() >= 1) {
+        return Duration::from_millis(0);
+    }
+    
+    let mut play_time_millis = user.daily_play_time.millis();
+    for (_, started_at) in user.active_sessions.iter() {
+        let counted_from = (*started_at).max(user.last_reset_day);
+        if let Some(duration) = current_time.duration_since(counted_from) {
+            play_time_millis = play_time_millis.saturating_add(duration.millis());
+        }
+    }
+    
+    Duration::from_millis(play_time_millis)
+}
+
+// Spending on one platform or category in the user's current day and month.
+// Spending recorded under an earlier reset (or due for a reset) counts as zero.
+fn scoped_spending(
+    user: &UserCompliance,
+    account: &ScopedAccount,
+    current_time: Timestamp,
+) -> (Amount, Amount) {
+    let day_elapsed = current_time
+        .duration_since(user.last_reset_day)
+        .is_some_and(|duration| duration.days() >= 1);
+    let month_elapsed = current_time
+        .duration_since(user.last_reset_month)
+        .is_some_and(|duration| duration.days() >= 30);
+    let day_current = !day_elapsed && account.day_started == user.last_reset_day;
+    let month_current = !month_elapsed && account.month_started == user.last_reset_month;
+    
+    let daily_spent = if day_current { account.daily_spent } else { Amount::zero() };
+    let monthly_spent = if month_current { account.monthly_spent } else { Amount::zero() };
+    
+    (daily_spent, monthly_spent)
+}
+
+// Sum of two amounts, for adding a bet to spending it is known to fit under.
+// Limit checks use exceeds_limit instead.
+fn add_amounts(a: Amount, b: Amount) -> Result<Amount, ContractError> {
+    a.micro_ccd
+        .checked_add(b.micro_ccd)
+        .map(Amount::from_micro_ccd)
+        .ok_or(ContractError::AmountOverflow)
+}
+
+// Whether spending the amount on top of what is already spent goes over the
+// limit. A total too large to represent is over every limit, so a huge amount
+// is refused like any other bet over the limit instead of wrapping around.
+fn exceeds_limit(spent: Amount, amount: Amount, limit: Amount) -> bool {
+    match spent.micro_ccd.checked_add(amount.micro_ccd) {
+        Some(total) => total > limit.micro_ccd,
+        None => true,
+    }
+}
+
+// Amount left under a limit, or zero once it is used up
+fn remaining_under(limit: Amount, spent: Amount) -> Amount {
+    limit.checked_sub(spent).unwrap_or_else(Amount::zero)
+}
+
+// Which limit of a platform or category account
+enum ScopedLimit {
+    Daily,
+    Monthly,
+}
+
+// The platform or category limit a bet of this amount would go over, if any
+fn scoped_limit_exceeded(
+    user: &UserCompliance,
+    account: &ScopedAccount,
+    amount: Amount,
+    current_time: Timestamp,
+) -> Option<ScopedLimit> {
+    let (daily_spent, monthly_spent) = scoped_spending(user, account, current_time);
+    if let Some(limit) = account.daily_limit {
+        if exceeds_limit(daily_spent, amount, limit) {
+            return Some(ScopedLimit::Daily);
+        }
+    }
+    if let Some(limit) = account.monthly_limit {
+        if exceeds_limit(monthly_spent, amount, limit) {
+            return Some(ScopedLimit::Monthly);
+        }
+    }
+    None
+}
+
+// Amount still allowed under a platform or category account's limits, or the
+// given allowance if that is smaller
+fn scoped_allowance(
+    user: &UserCompliance,
+    account: &ScopedAccount,
+    allowance: Amount,
+    current_time: Timestamp,
+) -> Amount {
+    let (daily_spent, monthly_spent) = scoped_spending(user, account, current_time);
+    let mut allowance = allowance;
+    if let Some(limit) = account.daily_limit {
+        allowance = allowance.min(remaining_under(limit, daily_spent));
+    }
+    if let Some(limit) = account.monthly_limit {
+        allowance = allowance.min(remaining_under(limit, monthly_spent));
+    }
+    allowance
+}
+
+// Platform or category account after a bet of this amount (a new account
+// without limits if the user had none in this scope)
+fn account_after_bet(
+    user: &UserCompliance,
+    account: Option<ScopedAccount>,
+    amount: Amount,
+    current_time: Timestamp,
+) -> Result<ScopedAccount, ContractError> {
+    let (limits, (daily_spent, monthly_spent)) = match &account {
+        Some(account) => (
+            (account.daily_limit, account.monthly_limit),
+            scoped_spending(user, account, current_time),
+        ),
+        None => ((None, None), (Amount::zero(), Amount::zero())),
+    };
+    Ok(ScopedAccount {
+        daily_limit: limits.0,
+        monthly_limit: limits.1,
+        daily_spent: add_amounts(daily_spent, amount)?,
+        monthly_spent: add_amounts(monthly_spent, amount)?,
+        day_started: user.last_reset_day,
+        month_started: user.last_reset_month,
+    })
+}
+
+// Whether the user has used up their daily play time
+fn session_time_reached(user: &UserCompliance, current_time: Timestamp) -> bool {
+    user.daily_time_limit
+        .is_some_and(|limit| current_play_time(user, current_time) >= limit)
+}
+
+// The user's exclusion from a platform or category, if it is still in force
+fn active_exclusion<K: Serialize>(
+    exclusions: &StateMap<K, ScopedExclusion, StateApi>,
+    key: &K,
+    current_time: Timestamp,
+) -> Option<ScopedExclusion> {
+    let exclusion = *exclusions.get(key)?;
+    if current_time < exclusion.until {
+        Some(exclusion)
+    } else {
+        None
+    }
+}
+
+// Exclude a user from all platforms until the given time (None for
+// indefinitely). An exclusion already in force that ends later is kept together
+// with its issuer and reason, so no one can shorten it. Returns the end and
+// source of the exclusion now in force.
+fn extend_exclusion(
+    user: &mut UserCompliance,
+    already_excluded: bool,
+    until: Option<Timestamp>,
+    source: ExclusionSource,
+    current_time: Timestamp,
+) -> (Option<Timestamp>, ExclusionSource) {
+    let keep_existing = already_excluded
+        && match (user.cooldown_until, until) {
+            (None, _) => true,
+            (Some(existing), Some(requested)) => {
+                existing > current_time && existing >= requested
+            }
+            (Some(_), None) => false,
+        };
+    
+    if keep_existing {
+        let existing_source = *user.exclusion_source.get_or_insert(source);
+        return (user.cooldown_until, existing_source);
+    }
+    
+    user.cooldown_until = until;
+    user.exclusion_source = Some(source);
+    (until, source)
+}
+
+// Exclusion to keep when a new one is imposed on top of an existing one. The
+// later end wins together with its issuer and reason, so an exclusion is
+// never shortened.
+fn merge_exclusion(
+    existing: Option<ScopedExclusion>,
+    new: ScopedExclusion,
+) -> ScopedExclusion {
+    match existing {
+        Some(existing) if existing.until >= new.until => existing,
+        _ => new,
+    }
+}
+
+// Self-exclude from specific platforms only, leaving the others available.
+// An existing exclusion from a platform is never shortened.
+#[receive(
+    contract = "safestake_registry",
+    name = "self_exclude_platforms",
+    parameter = "SelfExcludePlatformsParams",
+    error = "ContractError",
+    mutable,
+    enable_logger
+)]
+fn self_exclude_platforms(
+    ctx: &ReceiveContext,
+    host: &mut Host<State>,
+    logger: &mut Logger,
+) -> Result<(), ContractError> {
+    ensure_users_migrated(host.state())?;
+    let params: SelfExcludePlatformsParams = ctx.parameter_cursor().get()?;
+    
+    let sender_account = match ctx.sender() {
+        Address::Account(acc) => acc,
+        Address::Contract(_) => return Err(ContractError::ContractSenderNotAllowed),
+    };
+    let sender_hash = hash_account(sender_account);
+    
+    let current_time = ctx.metadata().slot_time();
+    let excluded_until = current_time
+        .checked_add(Duration::from_days(params.duration_days as u64))
+        .ok_or(ContractError::ParseParams)?;
+    
+    let mut user = host
+        .state_mut()
+        .registry
+        .get_mut(&sender_hash)
+        .ok_or(ContractError::UserNotRegistered)?;
+    
+    let exclusion = ScopedExclusion {
+        until: excluded_until,
+        source: ExclusionSource {
+            issuer: ExclusionIssuer::User,
+            reason: ExclusionReason::UserRequest,
+        },
+    };
+    for platform_id in params.platform_ids {
+        let existing = user.platform_exclusions.get(&platform_id).map(|e| *e);
+        let merged = merge_exclusion(existing, exclusion);
+        let _ = user.platform_exclusions.insert(platform_id.clone(), merged);
+        logger.log(&Event::ExclusionImposed(ExclusionEvent {
+            user_account: sender_account,
+            scope: ExclusionScope::Platform(platform_id),
+            until: Some(merged.until),
+            source: merged.source,
+        }))?;
+    }
+    
+    Ok(())
+}
+
+// Self-exclude from specific game categories only, on every platform.
+// An existing exclusion from a category is never shortened.
+#[receive(
+    contract = "safestake_registry",
+    name = "self_exclude_categories",
+    parameter = "SelfExcludeCategoriesParams",
+    error = "ContractError",
+    mutable,
+    enable_logger
+)]
+fn self_exclude_categories(
+    ctx: &ReceiveContext,
+    host: &mut Host<State>,
+    logger: &mut Logger,
+) -> Result<(), ContractError> {
+    ensure_users_migrated(host.state())?;
+    let params: SelfExcludeCategoriesParams = ctx.parameter_cursor().get()?;
+    
+    let sender_account = match ctx.sender() {
+        Address::Account(acc) => acc,
+        Address::Contract(_) => return Err(ContractError::ContractSenderNotAllowed),
+    };
+    let sender_hash = hash_account(sender_account);
+    
+    let current_time = ctx.metadata().slot_time();
+    let excluded_until = current_time
+        .checked_add(Duration::from_days(params.duration_days as u64))
+        .ok_or(ContractError::ParseParams)?;
+    
+    let mut user = host
+        .state_mut()
+        .registry
+        .get_mut(&sender_hash)
+        .ok_or(ContractError::UserNotRegistered)?;
+    
+    let exclusion = ScopedExclusion {
+        until: excluded_until,
+        source: ExclusionSource {
+            issuer: ExclusionIssuer::User,
+            reason: ExclusionReason::UserRequest,
+        },
+    };
+    for category in params.categories {
+        let existing = user.category_exclusions.get(&category).map(|e| *e);
+        let merged = merge_exclusion(existing, exclusion);
+        let _ = user.category_exclusions.insert(category, merged);
+        logger.log(&Event::ExclusionImposed(ExclusionEvent {
+            user_account: sender_account,
+            scope: ExclusionScope::Category(category),
+            until: Some(merged.until),
+            source: merged.source,
+        }))?;
+    }
+    
+    Ok(())
+}
+
+// Exclude a user from the calling operator's platform, e.g. after observing
+// markers of harm. Only registered operators may call this, and only for
+// their own platform.
+#[receive(
+    contract = "safestake_registry",
+    name = "impose_platform_exclusion",
+    parameter = "ImposePlatformExclusionParams",
+    error = "ContractError",
+    mutable,
+    enable_logger
+)]
+fn impose_platform_exclusion(
+    ctx: &ReceiveContext,
+    host: &mut Host<State>,
+    logger: &mut Logger,
+) -> Result<(), ContractError> {
+    ensure_users_migrated(host.state())?;
+    let params: ImposePlatformExclusionParams = ctx.parameter_cursor().get()?;
+    
+    let operator = match ctx.sender() {
+        Address::Account(acc) => acc,
+        Address::Contract(_) => return Err(ContractError::Unauthorized),
+    };
+    let platform_id = host
+        .state()
+        .operators
+        .get(&operator)
+        .map(|platform_id| platform_id.clone())
+        .ok_or(ContractError::Unauthorized)?;
+    
+    let current_time = ctx.metadata().slot_time();
+    let excluded_until = current_time
+        .checked_add(Duration::from_days(params.duration_days as u64))
+        .ok_or(ContractError::ParseParams)?;
+    
+    let mut user = host
+        .state_mut()
+        .registry
+        .get_mut(&hash_account(params.user_account))
+        .ok_or(ContractError::UserNotRegistered)?;
+    
+    let exclusion = ScopedExclusion {
+        until: excluded_until,
+        source: ExclusionSource {
+            issuer: ExclusionIssuer::Operator(operator),
+            reason: params.reason,
+        },
+    };
+    let existing = user.platform_exclusions.get(&platform_id).map(|e| *e);
+    let merged = merge_exclusion(existing, exclusion);
+    let _ = user.platform_exclusions.insert(platform_id.clone(), merged);
+    
+    logger.log(&Event::ExclusionImposed(ExclusionEvent {
+        user_account: params.user_account,
+        scope: ExclusionScope::Platform(platform_id),
+        until: Some(merged.until),
+        source: merged.source,
+    }))?;
+    
+    Ok(())
+}
+
+// Exclude a user from all platforms, e.g. to enforce a court order.
+// Only regulators may call this.
+#[receive(
+    contract = "safestake_registry",
+    name = "impose_exclusion",
+    parameter = "ImposeExclusionParams",
+    error = "ContractError",
+    mutable,
+    enable_logger
+)]
+fn impose_exclusion(
+    ctx: &ReceiveContext,
+    host: &mut Host<State>,
+    logger: &mut Logger,
+) -> Result<(), ContractError> {
+    ensure_users_migrated(host.state())?;
+    let params: ImposeExclusionParams = ctx.parameter_cursor().get()?;
+    
+    let regulator = match ctx.sender() {
+        Address::Account(acc) => acc,
+        Address::Contract(_) => return Err(ContractError::Unauthorized),
+    };
+    if !has_role(host.state(), ctx.sender(), Role::Regulator) {
+        return Err(ContractError::Unauthorized);
+    }
+    
+    let current_time = ctx.metadata().slot_time();
+    let requested_until = match params.duration_days {
+        Some(days) => Some(
+            current_time
+                .checked_add(Duration::from_days(days as u64))
+                .ok_or(ContractError::ParseParams)?,
+        ),
+        None => None,
+    };
+    
+    let user_hash = hash_account(params.user_account);
+    let was_excluded = host.state().excluded_users.contains(&user_hash);
+    
+    let mut user = host
+        .state_mut()
+        .registry
+        .get_mut(&user_hash)
+        .ok_or(ContractError::UserNotRegistered)?;
+    
+    let source = ExclusionSource {
+        issuer: ExclusionIssuer::Regulator(regulator),
+        reason: params.reason,
+    };
+    let (until, source) =
+        extend_exclusion(&mut user, was_excluded, requested_until, source, current_time);
+    drop(user);
+    host.state_mut().excluded_users.insert(user_hash);
+    
+    logger.log(&Event::ExclusionImposed(ExclusionEvent {
+        user_account: params.user_account,
+        scope: ExclusionScope::Global,
+        until,
+        source,
+    }))?;
+    
+    Ok(())
+}
+
+// Nominate a trusted contact (e.g. a family member) who may put the calling
+// user on a short cooldown, or remove one. Removing a contact does not end a
+// cooldown they already imposed.
+#[receive(
+    contract = "safestake_registry",
+    name = "set_trusted_contact",
+    parameter = "SetTrustedContactParams",
+    error = "ContractError",
+    mutable
+)]
+fn set_trusted_contact(
+    ctx: &ReceiveContext,
+    host: &mut Host<State>,
+) -> Result<(), ContractError> {
+    ensure_users_migrated(host.state())?;
+    let params: SetTrustedContactParams = ctx.parameter_cursor().get()?;
+    
+    if let Some(max_days) = params.max_cooldown_days {
+        if max_days == 0 || max_days > MAX_TRUSTED_COOLDOWN_DAYS {
+            return Err(ContractError::InvalidLimits);
+        }
+    }
+    
+    let sender_hash = match ctx.sender() {
+        Address::Account(acc) => hash_account(acc),
+        Address::Contract(_) => return Err(ContractError::ContractSenderNotAllowed),
+    };
+    
+    let mut user = host
+        .state_mut()
+        .registry
+        .get_mut(&sender_hash)
+        .ok_or(ContractError::UserNotRegistered)?;
+    
+    match params.max_cooldown_days {
+        Some(max_days) => {
+            let _ = user.trusted_contacts.insert(params.contact, max_days);
+        }
+        None => user.trusted_contacts.remove(&params.contact),
+    }
+    
+    Ok(())
+}
+
+// Put a user on a cooldown as one of their trusted contacts. The cooldown can
+// be no longer than the user consented to, and neither the user nor the
+// contact can cancel it. No contact can request another one until it ends.
+#[receive(
+    contract = "safestake_registry",
+    name = "request_cooldown",
+    parameter = "TrustedCooldownParams",
+    error = "ContractError",
+    mutable,
+    enable_logger
+)]
+fn request_cooldown(
+    ctx: &ReceiveContext,
+    host: &mut Host<State>,
+    logger: &mut Logger,
+) -> Result<(), ContractError> {
+    ensure_users_migrated(host.state())?;
+    let params: TrustedCooldownParams = ctx.parameter_cursor().get()?;
+    
+    let contact = match ctx.sender() {
+        Address::Account(acc) => acc,
+        Address::Contract(_) => return Err(ContractError::Unauthorized),
+    };
+    
+    let current_time = ctx.metadata().slot_time();
+    let user_hash = hash_account(params.user_account);
+    let was_excluded = host.state().excluded_users.contains(&user_hash);
+    
+    let mut user = host
+        .state_mut()
+        .registry
+        .get_mut(&user_hash)
+        .ok_or(ContractError::UserNotRegistered)?;
+    
+    let max_days = *user
+        .trusted_contacts
+        .get(&contact)
+        .ok_or(ContractError::Unauthorized)?;
+    if params.duration_days > max_days {
+        return Err(ContractError::CooldownTooLong);
+    }
+    
+    // Requesting again before a contact's cooldown ends would extend it past
+    // the consented maximum
+    let contact_cooldown_running = was_excluded
+        && user.cooldown_until.is_some_and(|until| current_time < until)
+        && matches!(
+            user.exclusion_source,
+            Some(ExclusionSource {
+                issuer: ExclusionIssuer::TrustedContact(_),
+                ..
+            })
+        );
+    if contact_cooldown_running {
+        return Err(ContractError::CooldownTooLong);
+    }
+    
+    let requested_until = current_time
+        .checked_add(Duration::from_days(params.duration_days as u64))
+        .ok_or(ContractError::ParseParams)?;
+    let source = ExclusionSource {
+        issuer: ExclusionIssuer::TrustedContact(contact),
+        reason: ExclusionReason::TrustedContactRequest,
+    };
+    let (until, source) =
+        extend_exclusion(&mut user, was_excluded, Some(requested_until), source, current_time);
+    drop(user);
+    host.state_mut().excluded_users.insert(user_hash);
+    
+    logger.log(&Event::ExclusionImposed(ExclusionEvent {
+        user_account: params.user_account,
+        scope: ExclusionScope::Global,
+        until,
+        source,
+    }))?;
+    
+    Ok(())
+}
+
+// Set or remove the calling user's automatic cooldown rule
+#[receive(
+    contract = "safestake_registry",
+    name = "set_auto_cooldown",
+    parameter = "SetAutoCooldownParams",
+    error = "ContractError",
+    mutable
+)]
+fn set_auto_cooldown(
+    ctx: &ReceiveContext,
+    host: &mut Host<State>,
+) -> Result<(), ContractError> {
+    ensure_users_migrated(host.state())?;
+    let params: SetAutoCooldownParams = ctx.parameter_cursor().get()?;
+    
+    if let Some(rule) = params.rule {
+        if rule.cooldown.millis() == 0 {
+            return Err(ContractError::InvalidLimits);
+        }
+        if rule.max_rejections.is_some()
+            && (rule.max_rejections == Some(0) || rule.rejection_window.millis() == 0)
+        {
+            return Err(ContractError::InvalidLimits);
+        }
+    }
+    
+    let sender_hash = match ctx.sender() {
+        Address::Account(acc) => hash_account(acc),
+        Address::Contract(_) => return Err(ContractError::ContractSenderNotAllowed),
+    };
+    let current_time = ctx.metadata().slot_time();
+    
+    let mut user = host
+        .state_mut()
+        .registry
+        .get_mut(&sender_hash)
+        .ok_or(ContractError::UserNotRegistered)?;
+    
+    user.auto_cooldown = params.rule;
+    user.rejected_attempts = 0;
+    user.rejection_window_start = current_time;
+    
+    Ok(())
+}
+
+// Whether a bet was rejected by one of the user's limits (as opposed to an
+// exclusion or a missing registration)
+fn is_limit_rejection(outcome: TransactionOutcome) -> bool {
+    matches!(
+        outcome,
+        TransactionOutcome::DailyLimitExceeded
+            | TransactionOutcome::MonthlyLimitExceeded
+            | TransactionOutcome::StakeTooHigh
+            | TransactionOutcome::BetCountLimitExceeded
+            | TransactionOutcome::SessionTimeLimitExceeded
+            | TransactionOutcome::PlatformDailyLimitExceeded
+            | TransactionOutcome::PlatformMonthlyLimitExceeded
+            | TransactionOutcome::CategoryDailyLimitExceeded
+            | TransactionOutcome::CategoryMonthlyLimitExceeded
+    )
+}
+
+// Outcome reported to the operator for the result of record_bet. Errors that
+// are not about the bet itself are passed on and abort the call.
+fn transaction_outcome(
+    result: Result<(), ContractError>,
+) -> Result<TransactionOutcome, ContractError> {
+    let outcome = match result {
+        Ok(()) => TransactionOutcome::Recorded,
+        Err(ContractError::DailyLimitExceeded) => TransactionOutcome::DailyLimitExceeded,
+        Err(ContractError::MonthlyLimitExceeded) => TransactionOutcome::MonthlyLimitExceeded,
+        Err(ContractError::SelfExcluded) => TransactionOutcome::SelfExcluded,
+        Err(ContractError::OnCooldown) => TransactionOutcome::OnCooldown,
+        Err(ContractError::UserNotRegistered) => TransactionOutcome::NotRegistered,
+        Err(ContractError::AgeNotVerified) => TransactionOutcome::AgeNotVerified,
+        Err(ContractError::StakeTooHigh) => TransactionOutcome::StakeTooHigh,
+        Err(ContractError::BetCountLimitExceeded) => TransactionOutcome::BetCountLimitExceeded,
+        Err(ContractError::SessionTimeLimitExceeded) => {
+            TransactionOutcome::SessionTimeLimitExceeded
+        }
+        Err(ContractError::PlatformDailyLimitExceeded) => {
+            TransactionOutcome::PlatformDailyLimitExceeded
+        }
+        Err(ContractError::PlatformMonthlyLimitExceeded) => {
+            TransactionOutcome::PlatformMonthlyLimitExceeded
+        }
+        Err(ContractError::PlatformExcluded) => TransactionOutcome::PlatformExcluded,
+        Err(ContractError::CategoryDailyLimitExceeded) => {
+            TransactionOutcome::CategoryDailyLimitExceeded
+        }
+        Err(ContractError::CategoryMonthlyLimitExceeded) => {
+            TransactionOutcome::CategoryMonthlyLimitExceeded
+        }
+        Err(ContractError::CategoryExcluded) => TransactionOutcome::CategoryExcluded,
+        Err(ContractError::AffordabilityCheckRequired) => {
+            TransactionOutcome::AffordabilityCheckRequired
+        }
+        Err(ContractError::PendingReactivation) => TransactionOutcome::PendingReactivation,
+        Err(e) => return Err(e),
+    };
+    Ok(outcome)
+}
+
+// Apply a single bet, remember it if it was rejected and then apply the
+// user's automatic cooldown rule.
+// Shared by record_transaction and record_transactions_batch so both enforce
+// age verification, limits and exclusions the same way.
+// Both only accept bets from registered operators, so nobody else can add
+// rejections, bet counts or risk indicators to a user.
+fn apply_transaction(
+    state: &mut State,
+    logger: &mut Logger,
+    platform_id: &str,
+    bet: RecordTransactionParams,
+    current_time: Timestamp,
+) -> Result<TransactionOutcome, ContractError> {
+    let user_account = bet.user_account;
+    let user_hash = hash_account(user_account);
+    let outcome = transaction_outcome(record_bet(state, platform_id, &bet, current_time))?;
+    
+    let rejected = outcome != TransactionOutcome::Recorded
+        && outcome != TransactionOutcome::NotRegistered;
+    if rejected {
+        if let Some(mut user) = state.registry.get_mut(&user_hash) {
+            reset_elapsed_periods(&mut user, current_time);
+            user.hourly_rejections = user.hourly_rejections.saturating_add(1);
+            user.daily_rejections = user.daily_rejections.saturating_add(1);
+            user.last_rejection = Some(current_time);
+        }
+        logger.log(&Event::BetRejected(BetRejectedEvent {
+            user_account,
+            amount: bet.amount,
+            outcome,
+        }))?;
+    } else if outcome == TransactionOutcome::Recorded {
+        let scope = PruneScope::Bet(platform_id, bet.category);
+        prune_exclusions(state, logger, user_account, scope, current_time)?;
+    }
+    
+    apply_auto_cooldown(state, logger, user_account, outcome, current_time)?;
+    Ok(outcome)
+}
+
+// Start the user's automatic cooldown if the bet used up a limit or was one
+// rejection too many under their rule
+fn apply_auto_cooldown(
+    state: &mut State,
+    logger: &mut Logger,
+    user_account: AccountAddress,
+    outcome: TransactionOutcome,
+    current_time: Timestamp,
+) -> Result<(), ContractError> {
+    let counts_as_rejection = is_limit_rejection(outcome);
+    if outcome != TransactionOutcome::Recorded && !counts_as_rejection {
+        return Ok(());
+    }
+    
+    let user_hash = hash_account(user_account);
+    let was_excluded = state.excluded_users.contains(&user_hash);
+    let mut user = match state.registry.get_mut(&user_hash) {
+        Some(user) => user,
+        None => return Ok(()),
+    };
+    let rule = match user.auto_cooldown {
+        Some(rule) => rule,
+        None => return Ok(()),
+    };
+    
+    let triggered = if counts_as_rejection {
+        match rule.max_rejections {
+            Some(max_rejections) => {
+                let window_elapsed = current_time
+                    .duration_since(user.rejection_window_start)
+                    .is_some_and(|duration| duration >= rule.rejection_window);
+                if window_elapsed {
+                    user.rejected_attempts = 0;
+                    user.rejection_window_start = current_time;
+                }
+                user.rejected_attempts += 1;
+                user.rejected_attempts >= max_rejections
+            }
+            None => false,
+        }
+    } else {
+        rule.on_limit_reached
+            && (user.daily_spent >= user.daily_limit || user.monthly_spent >= user.monthly_limit)
+    };
+    if !triggered {
+        return Ok(());
+    }
+    
+    let cooldown_until = current_time
+        .checked_add(rule.cooldown)
+        .ok_or(ContractError::ParseParams)?;
+    let source = ExclusionSource {
+        issuer: ExclusionIssuer::User,
+        reason: ExclusionReason::AutoCooldown,
+    };
+    user.rejected_attempts = 0;
+    user.rejection_window_start = current_time;
+    let (until, source) =
+        extend_exclusion(&mut user, was_excluded, Some(cooldown_until), source, current_time);
+    drop(user);
+    state.excluded_users.insert(user_hash);
+    
+    logger.log(&Event::ExclusionImposed(ExclusionEvent {
+        user_account,
+        scope: ExclusionScope::Global,
+        until,
+        source,
+    }))?;
+    
+    Ok(())
+}
+
+// Error record_transaction reports for a bet the eligibility engine refused,
+// or None if the bet may go ahead
+fn refusal_error(status: EligibilityStatus) -> Option<ContractError> {
+    let error = match status {
+        EligibilityStatus::Eligible | EligibilityStatus::ReviewRequired => return None,
+        EligibilityStatus::DailyLimitReached => ContractError::DailyLimitExceeded,
+        EligibilityStatus::MonthlyLimitReached => ContractError::MonthlyLimitExceeded,
+        EligibilityStatus::SelfExcluded => ContractError::SelfExcluded,
+        EligibilityStatus::OnCooldown => ContractError::OnCooldown,
+        EligibilityStatus::NotRegistered => ContractError::UserNotRegistered,
+        EligibilityStatus::AgeNotVerified => ContractError::AgeNotVerified,
+        EligibilityStatus::StakeTooHigh => ContractError::StakeTooHigh,
+        EligibilityStatus::BetCountLimitReached => ContractError::BetCountLimitExceeded,
+        EligibilityStatus::SessionTimeLimitReached => ContractError::SessionTimeLimitExceeded,
+        EligibilityStatus::PlatformDailyLimitReached => ContractError::PlatformDailyLimitExceeded,
+        EligibilityStatus::PlatformMonthlyLimitReached => {
+            ContractError::PlatformMonthlyLimitExceeded
+        }
+        EligibilityStatus::PlatformExcluded => ContractError::PlatformExcluded,
+        EligibilityStatus::CategoryDailyLimitReached => ContractError::CategoryDailyLimitExceeded,
+        EligibilityStatus::CategoryMonthlyLimitReached => {
+            ContractError::CategoryMonthlyLimitExceeded
+        }
+        EligibilityStatus::CategoryExcluded => ContractError::CategoryExcluded,
+        EligibilityStatus::AffordabilityCheckRequired => ContractError::AffordabilityCheckRequired,
+        EligibilityStatus::PendingReactivation => ContractError::PendingReactivation,
+        EligibilityStatus::TransactionsPaused => ContractError::Paused,
+    };
+    Some(error)
+}
+
+// Check a single bet with the eligibility engine and, if it is allowed, add
+// it to the user's spending record
+fn record_bet(
+    state: &mut State,
+    platform_id: &str,
+    bet: &RecordTransactionParams,
+    current_time: Timestamp,
+) -> Result<(), ContractError> {
+    let amount = bet.amount;
+    let category = bet.category;
+    let check = CheckEligibilityParams {
+        user_account: bet.user_account,
+        proposed_amount: amount,
+        platform_id: Some(String::from(platform_id)),
+        category: Some(category),
+    };
+    if let Some(error) = refusal_error(evaluate_bet(state, &check, current_time)?) {
+        return Err(error);
+    }
+    
+    let user_hash = hash_account(bet.user_account);
+    let mut user = state
+        .registry
+        .get_mut(&user_hash)
+        .ok_or(ContractError::UserNotRegistered)?;
+    
+    reset_elapsed_periods(&mut user, current_time);
+    
+    let platform_key = String::from(platform_id);
+    let platform_account = user.platform_accounts.get(&platform_key).map(|a| a.clone());
+    let category_account = user.category_accounts.get(&category).map(|a| a.clone());
+    
+    // Record the transaction
+    user.daily_spent = add_amounts(user.daily_spent, amount)?;
+    user.monthly_spent = add_amounts(user.monthly_spent, amount)?;
+    user.hourly_bets += 1;
+    user.daily_bets += 1;
+    let commitment = bet.platform_commitment;
+    // A zero-amount bet (e.g. a free spin) says nothing about the user's behaviour
+    if amount > Amount::zero() {
+        add_daily_spending(&mut user, amount, current_time);
+        let first_bet_on_platform_today = user.platforms_bet_today.insert(commitment);
+        update_risk_profile(&mut user, amount, first_bet_on_platform_today, current_time);
+    }
+    // Spending is only tracked on platforms the user has set limits on
+    if let Some(account) = platform_account {
+        let account = account_after_bet(&user, Some(account), amount, current_time)?;
+        let _ = user.platform_accounts.insert(platform_key, account);
+    }
+    let category_account = account_after_bet(&user, category_account, amount, current_time)?;
+    let _ = user.category_accounts.insert(category, category_account);
+    if user.platforms_used.insert(commitment) {
+        user.platforms_used_count = user.platforms_used_count.saturating_add(1);
+    }
+    
+    Ok(())
+}
+
+// Which of a user's platform and category exclusions prune_exclusions looks
+// at. Users choose how many platforms they exclude themselves from, so they
+// are never all looked at unbounded.
+enum PruneScope<'a> {
+    // Only those a bet on this platform and game category falls under
+    Bet(&'a str, GameCategory),
+    // The first ones in storage order, up to this many, counted down as they
+    // are looked at
+    Budget(&'a mut usize),
+}
+
+// Keys of the exclusions that have ended among the first ones in storage
+// order, looking at no more than the budget allows
+fn expired_exclusions<K: Serialize + Clone>(
+    exclusions: &StateMap<K, ScopedExclusion, StateApi>,
+    budget: &mut usize,
+    current_time: Timestamp,
+) -> Vec<K> {
+    let mut visited = 0;
+    let expired = exclusions
+        .iter()
+        .take(*budget)
+        .inspect(|_| visited += 1)
+        .filter(|(_, exclusion)| current_time >= exclusion.until)
+        .map(|(key, _)| key.clone())
+        .collect();
+    *budget -= visited;
+    expired
+}
+
+// Remove a user's exclusions in the given scope that have ended: the
+// exclusion from all platforms once its cooldown is over, and expired platform
+// and category exclusions. Indefinite exclusions are never removed. Logs an
+// ExclusionEnded event for each removed exclusion and returns whether there
+// were any.
+fn prune_exclusions(
+    state: &mut State,
+    logger: &mut Logger,
+    user_account: AccountAddress,
+    scope: PruneScope,
+    current_time: Timestamp,
+) -> Result<bool, ContractError> {
+    let user_hash = hash_account(user_account);
+    let mut user = match state.registry.get_mut(&user_hash) {
+        Some(user) => user,
+        None => return Ok(false),
+    };
+    
+    let mut ended = Vec::new();
+    if user.cooldown_until.is_some_and(|until| current_time >= until) {
+        user.cooldown_until = None;
+        user.exclusion_source = None;
+        state.excluded_users.remove(&user_hash);
+        ended.push(ExclusionScope::Global);
+    }
+    
+    let (expired_platforms, expired_categories) = match scope {
+        PruneScope::Bet(platform_id, category) => {
+            let platform_id = platform_id.to_string();
+            let platform_expired = user
+                .platform_exclusions
+                .get(&platform_id)
+                .is_some_and(|exclusion| current_time >= exclusion.until);
+            let category_expired = user
+                .category_exclusions
+                .get(&category)
+                .is_some_and(|exclusion| current_time >= exclusion.until);
+            (
+                if platform_expired { vec![platform_id] } else { Vec::new() },
+                if category_expired { vec![category] } else { Vec::new() },
+            )
+        }
+        PruneScope::Budget(budget) => (
+            expired_exclusions(&user.platform_exclusions, budget, current_time),
+            expired_exclusions(&user.category_exclusions, budget, current_time),
+        ),
+    };
+    
+    for platform_id in expired_platforms {
+        user.platform_exclusions.remove(&platform_id);
+        ended.push(ExclusionScope::Platform(platform_id));
+    }
+    for category in expired_categories {
+        user.category_exclusions.remove(&category);
+        ended.push(ExclusionScope::Category(category));
+    }
+    
+    let pruned = !ended.is_empty();
+    for scope in ended {
+        logger.log(&Event::ExclusionEnded(ExclusionEndedEvent {
+            user_account,
+            reactivation_required: scope == ExclusionScope::Global && user.reactivation_required,
+            scope,
+        }))?;
+    }
+    Ok(pruned)
+}
+
+// Remove expired exclusions of the given users, so excluded_users and the
+// users' platform and category exclusions only hold exclusions in force.
+// Anyone may call this; users without expired exclusions are skipped.
+// Returns how many of the users had exclusions removed.
+// At most MAX_PRUNE_ENTRIES platform and category exclusions are looked at
+// per call, in the order the users are given and each user's exclusions in
+// storage order. Those of a user with more are not all reached, but an
+// expired one is also removed by the next bet that it would have covered.
+#[receive(
+    contract = "safestake_registry",
+    name = "prune_expired_exclusions",
+    parameter = "PruneExclusionsParams",
+    return_value = "u32",
+    error = "ContractError",
+    mutable,
+    enable_logger
+)]
+fn prune_expired_exclusions(
+    ctx: &ReceiveContext,
+    host: &mut Host<State>,
+    logger: &mut Logger,
+) -> Result<u32, ContractError> {
+    ensure_users_migrated(host.state())?;
+    let params: PruneExclusionsParams = ctx.parameter_cursor().get()?;
+    let current_time = ctx.metadata().slot_time();
+    
+    if params.users.len() > MAX_PRUNE_BATCH {
+        return Err(ContractError::BatchTooLarge);
+    }
+    
+    let mut pruned = 0;
+    let mut budget = MAX_PRUNE_ENTRIES;
+    for account in params.users {
+        let scope = PruneScope::Budget(&mut budget);
+        if prune_exclusions(host.state_mut(), logger, account, scope, current_time)? {
+            pruned += 1;
+        }
+    }
+    Ok(pruned)
+}
+
+// View the exclusions of a user that are currently in force
+#[receive(
+    contract = "safestake_registry",
+    name = "get_active_exclusions",
+    parameter = "AccountAddress",
+    return_value = "ActiveExclusions",
+    error = "ContractError"
+)]
+fn get_active_exclusions(
+    ctx: &ReceiveContext,
+    host: &Host<State>,
+) -> Result<ActiveExclusions, ContractError> {
+    let account: AccountAddress = ctx.parameter_cursor().get()?;
+    let current_time = ctx.metadata().slot_time();
+    let user_hash = hash_account(account);
+    let state = host.state();
+    
+    let listed = state.excluded_users.contains(&user_hash);
+    let user = match state.registry.get(&user_hash) {
+        Some(user) => user,
+        None => {
+            // Excluded before registering, which has no end
+            return Ok(ActiveExclusions {
+                global: listed.then_some((None, None)),
+                platforms: Vec::new(),
+                categories: Vec::new(),
+            });
+        }
+    };
+    
+    let global = match user.cooldown_until {
+        Some(until) if listed && current_time < until => Some((Some(until), user.exclusion_source)),
+        None if listed => Some((None, user.exclusion_source)),
+        _ => None,
+    };
+    let platforms = user
+        .platform_exclusions
+        .iter()
+        .filter(|(_, exclusion)| current_time < exclusion.until)
+        .map(|(platform_id, exclusion)| (platform_id.clone(), *exclusion))
+        .collect();
+    let categories = user
+        .category_exclusions
+        .iter()
+        .filter(|(_, exclusion)| current_time < exclusion.until)
+        .map(|(category, exclusion)| (*category, *exclusion))
+        .collect();
+    
+    Ok(ActiveExclusions {
+        global,
+        platforms,
+        categories,
+    })
+}
+
+// Update the risk indicators with a recorded bet and recompute the risk score.
+// Call after the bet has been added to the user's spending.
+fn update_risk_profile(
+    user: &mut UserCompliance,
+    amount: Amount,
+    first_bet_on_platform_today: bool,
+    current_time: Timestamp,
+) {
+    let risk = &mut user.risk;
+    
+    if amount > risk.last_stake && risk.last_stake > Amount::zero() {
+        risk.stake_increases = risk.stake_increases.saturating_add(1);
+    } else {
+        risk.stake_increases = 0;
+    }
+    risk.last_stake = amount;
+    
+    let millis_into_day = current_time.timestamp_millis() % (24 * 60 * 60 * 1000);
+    if millis_into_day < 6 * 60 * 60 * 1000 {
+        risk.late_night_bets = risk.late_night_bets.saturating_add(1);
+    }
+    
+    if first_bet_on_platform_today {
+        risk.platforms_today = risk.platforms_today.saturating_add(1);
+    }
+    
+    let average = risk.average_daily_spend.micro_ccd;
+    let indicators = [
+        average > 0
+            && user.daily_spent.micro_ccd > average.saturating_mul(RISK_SPEND_ACCELERATION),
+        risk.stake_increases >= RISK_STAKE_INCREASES,
+        risk.late_night_bets >= RISK_LATE_NIGHT_BETS,
+        risk.platforms_today >= RISK_PLATFORMS_PER_DAY,
+    ];
+    risk.score = indicators.iter().filter(|present| **present).count() as u8 * RISK_POINTS;
+}
+
+// Record a gambling transaction and update user's spending.
+// Only registered operators may call this, and the bet is placed on the
+// operator's own platform. A rejected bet is reported as an outcome rather
+// than an error, so the rejection itself is remembered.
+#[receive(
+    contract = "safestake_registry",
+    name = "record_transaction",
+    parameter = "RecordTransactionParams",
+    return_value = "TransactionOutcome",
+    error = "ContractError",
+    mutable,
+    enable_logger
+)]
+fn record_transaction(
+    ctx: &ReceiveContext,
+    host: &mut Host<State>,
+    logger: &mut Logger,
+) -> Result<TransactionOutcome, ContractError> {
+    ensure_users_migrated(host.state())?;
+    let params: RecordTransactionParams = ctx.parameter_cursor().get()?;
+    let current_time = ctx.metadata().slot_time();
+    
+    if host.state().paused.transactions {
+        return Err(ContractError::Paused);
+    }
+    let platform_id = operator_platform(host.state(), ctx.sender())?;
+    
+    apply_transaction(host.state_mut(), logger, &platform_id, params, current_time)
+}
+
+// Record many gambling transactions on the calling operator's platform in a
+// single call.
+// Each entry is applied independently, so a rejected bet does not roll back
+// the rest of the batch; the outcome of every entry is returned in order.
+#[receive(
+    contract = "safestake_registry",
+    name = "record_transactions_batch",
+    parameter = "RecordTransactionsBatchParams",
+    return_value = "Vec<BatchTransactionResult>",
+    error = "ContractError",
+    mutable,
+    enable_logger
+)]
+fn record_transactions_batch(
+    ctx: &ReceiveContext,
+    host: &mut Host<State>,
+    logger: &mut Logger,
+) -> Result<Vec<BatchTransactionResult>, ContractError> {
+    ensure_users_migrated(host.state())?;
+    let params: RecordTransactionsBatchParams = ctx.parameter_cursor().get()?;
+    let current_time = ctx.metadata().slot_time();
+    
+    if host.state().paused.transactions {
+        return Err(ContractError::Paused);
+    }
+    let platform_id = operator_platform(host.state(), ctx.sender())?;
+    
+    let mut results = Vec::with_capacity(params.entries.len());
+    for entry in params.entries {
+        let bet = RecordTransactionParams {
+            user_account: entry.user_account,
+            amount: entry.amount,
+            platform_commitment: entry.platform_commitment,
+            category: entry.category,
         };
-       let _ = host.state_mut().registry.insert(sender_hash, new_user);
-    } else {
-        // Update existing user
-        let mut user = host.state_mut().registry.get_mut(&sender_hash).unwrap();
-        user.daily_limit = params.daily_limit;
-        user.monthly_limit = params.monthly_limit;
+        let outcome = apply_transaction(host.state_mut(), logger, &platform_id, bet, current_time)?;
+        results.push(BatchTransactionResult {
+            bet_id: entry.bet_id,
+            outcome,
+        });
     }
     
-    Ok(())
+    Ok(results)
 }
 
-// Self-exclude from all gambling platforms for a specified duration
+// Start a play session on a platform, reported by the platform's operator.
+// Fails if the user has already used up their daily play time on any platform.
 #[receive(
     contract = "safestake_registry",
-    name = "self_exclude",
-    parameter = "SelfExcludeParams",
+    name = "start_session",
+    parameter = "SessionParams",
     error = "ContractError",
     mutable
 )]
-fn self_exclude(
+fn start_session(
     ctx: &ReceiveContext,
     host: &mut Host<State>,
 ) -> Result<(), ContractError> {
-    let params: SelfExcludeParams = ctx.parameter_cursor().get()?;
+    ensure_users_migrated(host.state())?;
+    let params: SessionParams = ctx.parameter_cursor().get()?;
+    let user_hash = hash_account(params.user_account);
+    let current_time = ctx.metadata().slot_time();
     
-    let sender = ctx.sender();
-    let sender_hash = match sender {
-        Address::Account(acc) => hash_account(acc),
-        Address::Contract(_) => return Err(ContractError::ParseParams),
-    };
+    if !is_platform_operator(host.state(), ctx.sender(), &params.platform_id) {
+        return Err(ContractError::Unauthorized);
+    }
     
-    host.state_mut().excluded_users.insert(sender_hash);
+    let mut user = host
+        .state_mut()
+        .registry
+        .get_mut(&user_hash)
+        .ok_or(ContractError::UserNotRegistered)?;
     
-    let current_time = ctx.metadata().slot_time();
-    let duration_millis = params.duration_days as u64 * 24 * 60 * 60 * 1000;
-    let cooldown_until = current_time.checked_add(Duration::from_millis(duration_millis))
-        .ok_or(ContractError::ParseParams)?;
+    if !user.age_verified {
+        return Err(ContractError::AgeNotVerified);
+    }
     
-    if let Some(mut user) = host.state_mut().registry.get_mut(&sender_hash) {
-        user.cooldown_until = Some(cooldown_until);
+    reset_elapsed_periods(&mut user, current_time);
+    
+    if user.active_sessions.get(&params.platform_id).is_some() {
+        return Err(ContractError::SessionAlreadyActive);
+    }
+    
+    if session_time_reached(&user, current_time) {
+        return Err(ContractError::SessionTimeLimitExceeded);
     }
     
+    let _ = user.active_sessions.insert(params.platform_id, current_time);
     Ok(())
 }
 
-// Record a gambling transaction and update user's spending.
+// End a play session on a platform and add its length to today's play time.
+// Like start_session, only the platform's operator may report it.
 #[receive(
     contract = "safestake_registry",
-    name = "record_transaction",
-    parameter = "RecordTransactionParams",
+    name = "end_session",
+    parameter = "SessionParams",
     error = "ContractError",
     mutable
 )]
-fn record_transaction(
+fn end_session(
     ctx: &ReceiveContext,
     host: &mut Host<State>,
 ) -> Result<(), ContractError> {
-    let params: RecordTransactionParams = ctx.parameter_cursor().get()?;
+    ensure_users_migrated(host.state())?;
+    let params: SessionParams = ctx.parameter_cursor().get()?;
     let user_hash = hash_account(params.user_account);
     let current_time = ctx.metadata().slot_time();
     
-    // Check if user is excluded BEFORE getting mutable borrow
-    let is_excluded = host.state().excluded_users.contains(&user_hash);
+    if !is_platform_operator(host.state(), ctx.sender(), &params.platform_id) {
+        return Err(ContractError::Unauthorized);
+    }
     
-    // Get mutable reference to user
     let mut user = host
         .state_mut()
         .registry
         .get_mut(&user_hash)
         .ok_or(ContractError::UserNotRegistered)?;
     
-    // Check age verification
-    if !user.age_verified {
-        return Err(ContractError::AgeNotVerified);
+    reset_elapsed_periods(&mut user, current_time);
+    
+    let started_at = user
+        .active_sessions
+        .remove_and_get(&params.platform_id)
+        .ok_or(ContractError::SessionNotFound)?;
+    
+    // Only the part of the session after the last daily reset counts towards today
+    let counted_from = started_at.max(user.last_reset_day);
+    if let Some(duration) = current_time.duration_since(counted_from) {
+        user.daily_play_time = Duration::from_millis(
+            user.daily_play_time.millis().saturating_add(duration.millis()),
+        );
     }
     
-    // Check if daily reset is needed
+    Ok(())
+}
+
+// View a user's play time today and open sessions
+#[receive(
+    contract = "safestake_registry",
+    name = "get_play_time",
+    parameter = "AccountAddress",
+    return_value = "PlayTimeInfo",
+    error = "ContractError"
+)]
+fn get_play_time(
+    ctx: &ReceiveContext,
+    host: &Host<State>,
+) -> Result<PlayTimeInfo, ContractError> {
+    let account: AccountAddress = ctx.parameter_cursor().get()?;
+    let current_time = ctx.metadata().slot_time();
+    
+    let user = host
+        .state()
+        .registry
+        .get(&hash_account(account))
+        .ok_or(ContractError::UserNotRegistered)?;
+    
+    let active_sessions = user
+        .active_sessions
+        .iter()
+        .map(|(platform_id, started_at)| (platform_id.clone(), *started_at))
+        .collect();
+    
+    Ok(PlayTimeInfo {
+        play_time_today: current_play_time(&user, current_time),
+        daily_time_limit: user.daily_time_limit,
+        active_sessions,
+    })
+}
+
+// Whether the sender may read a user's harm indicators: the user themselves or an auditor
+fn can_view_user(state: &State, sender: Address, account: AccountAddress) -> bool {
+    sender == Address::Account(account) || has_role(state, sender, Role::Auditor)
+}
+
+// View a user's risk indicators and score (the user or auditors only)
+#[receive(
+    contract = "safestake_registry",
+    name = "get_risk_profile",
+    parameter = "AccountAddress",
+    return_value = "RiskProfile",
+    error = "ContractError"
+)]
+fn get_risk_profile(
+    ctx: &ReceiveContext,
+    host: &Host<State>,
+) -> Result<RiskProfile, ContractError> {
+    let account: AccountAddress = ctx.parameter_cursor().get()?;
+    
+    if !can_view_user(host.state(), ctx.sender(), account) {
+        return Err(ContractError::Unauthorized);
+    }
+    
+    let user = host
+        .state()
+        .registry
+        .get(&hash_account(account))
+        .ok_or(ContractError::UserNotRegistered)?;
+    
+    Ok(user.risk)
+}
+
+// View a user's platform history (the user or auditors only). The salt is
+// created off-chain when the user registers and stays with the user's wallet;
+// hashing sha256(salt || platform ID) for a platform and finding it among the
+// commitments shows the user gambled there. The list is only as faithful as
+// the commitments operators passed, and the bets themselves stay public.
+#[receive(
+    contract = "safestake_registry",
+    name = "get_platform_history",
+    parameter = "AccountAddress",
+    return_value = "PlatformHistory",
+    error = "ContractError"
+)]
+fn get_platform_history(
+    ctx: &ReceiveContext,
+    host: &Host<State>,
+) -> Result<PlatformHistory, ContractError> {
+    let account: AccountAddress = ctx.parameter_cursor().get()?;
+    
+    if !can_view_user(host.state(), ctx.sender(), account) {
+        return Err(ContractError::Unauthorized);
+    }
+    
+    let user = host
+        .state()
+        .registry
+        .get(&hash_account(account))
+        .ok_or(ContractError::UserNotRegistered)?;
+    
+    Ok(PlatformHistory {
+        commitments: user.platforms_used.iter().map(|commitment| *commitment).collect(),
+        count: user.platforms_used_count,
+    })
+}
+
+// View a user's rejected bets this hour and today, e.g. for harm monitoring
+// (the user or auditors only)
+#[receive(
+    contract = "safestake_registry",
+    name = "get_rejected_attempts",
+    parameter = "AccountAddress",
+    return_value = "RejectedAttemptsInfo",
+    error = "ContractError"
+)]
+fn get_rejected_attempts(
+    ctx: &ReceiveContext,
+    host: &Host<State>,
+) -> Result<RejectedAttemptsInfo, ContractError> {
+    let account: AccountAddress = ctx.parameter_cursor().get()?;
+    let current_time = ctx.metadata().slot_time();
+    
+    if !can_view_user(host.state(), ctx.sender(), account) {
+        return Err(ContractError::Unauthorized);
+    }
+    
+    let user = host
+        .state()
+        .registry
+        .get(&hash_account(account))
+        .ok_or(ContractError::UserNotRegistered)?;
+    
+    // Counts from a period that is due for a reset no longer apply
+    let hour_elapsed = current_time
+        .duration_since(user.last_reset_hour)
+        .is_some_and(|duration| duration.hours() >= 1);
+    let day_elapsed = current_time
+        .duration_since(user.last_reset_day)
+        .is_some_and(|duration| duration.days() >= 1);
+    
+    Ok(RejectedAttemptsInfo {
+        this_hour: if hour_elapsed { 0 } else { user.hourly_rejections },
+        today: if day_elapsed { 0 } else { user.daily_rejections },
+        last_rejected_at: user.last_rejection,
+    })
+}
+
+// Spending in the current day and month, treating a period that is due for a
+// reset as already reset (read-only counterpart of the reset in apply_transaction).
+fn current_spending(user: &UserCompliance, current_time: Timestamp) -> (Amount, Amount) {
+    let mut daily_spent = user.daily_spent;
+    let mut monthly_spent = user.monthly_spent;
+    
     let time_since_daily = current_time.duration_since(user.last_reset_day);
     if let Some(duration) = time_since_daily {
         if duration.days() >= 1 {
-            user.daily_spent = Amount::zero();
-            user.last_reset_day = current_time;
+            daily_spent = Amount::zero();
         }
     }
     
-    // Check if monthly reset is needed
     let time_since_monthly = current_time.duration_since(user.last_reset_month);
     if let Some(duration) = time_since_monthly {
         if duration.days() >= 30 {
-            user.monthly_spent = Amount::zero();
-            user.last_reset_month = current_time;
+            monthly_spent = Amount::zero();
         }
     }
     
-    // Check limits
-    if user.daily_spent.micro_ccd + params.amount.micro_ccd > user.daily_limit.micro_ccd {
-        return Err(ContractError::DailyLimitExceeded);
-    }
-    
-    if user.monthly_spent.micro_ccd + params.amount.micro_ccd > user.monthly_limit.micro_ccd {
-        return Err(ContractError::MonthlyLimitExceeded);
-    }
+    (daily_spent, monthly_spent)
+}
+
+// Bets placed this hour and today, treating a period that is due for a reset as
+// already reset
+fn current_bet_counts(user: &UserCompliance, current_time: Timestamp) -> (u32, u32) {
+    let mut hourly_bets = user.hourly_bets;
+    let mut daily_bets = user.daily_bets;
     
-    // Check if user is excluded
-    if is_excluded {
-        if let Some(cooldown_until) = user.cooldown_until {
-            if current_time < cooldown_until {
-                return Err(ContractError::OnCooldown);
-            }
-            // Cooldown ended - we'll remove from excluded set after releasing user borrow
-        } else {
-            return Err(ContractError::SelfExcluded);
+    let time_since_hourly = current_time.duration_since(user.last_reset_hour);
+    if let Some(duration) = time_since_hourly {
+        if duration.hours() >= 1 {
+            hourly_bets = 0;
         }
     }
     
-    // Record the transaction
-    user.daily_spent.micro_ccd += params.amount.micro_ccd;
-    user.monthly_spent.micro_ccd += params.amount.micro_ccd;
-    user.platforms_used.insert(params.platform_id);
-    
-    // If cooldown ended, remove from excluded set
-    if is_excluded {
-        if let Some(cooldown_until) = user.cooldown_until {
-            if current_time >= cooldown_until {
-                // Drop the user borrow first
-                drop(user);
-                host.state_mut().excluded_users.remove(&user_hash);
-                // Re-borrow to clear cooldown
-                if let Some(mut user) = host.state_mut().registry.get_mut(&user_hash) {
-                    user.cooldown_until = None;
-                }
-            }
+    let time_since_daily = current_time.duration_since(user.last_reset_day);
+    if let Some(duration) = time_since_daily {
+        if duration.days() >= 1 {
+            daily_bets = 0;
         }
     }
     
-    Ok(())
+    (hourly_bets, daily_bets)
 }
 
-// Check if a user is eligible to place a bet 
-#[receive(
-    contract = "safestake_registry",
-    name = "check_eligibility",
-    parameter = "CheckEligibilityParams",
-    return_value = "EligibilityStatus",
-    error = "ContractError"
-)]
-fn check_eligibility(
-    ctx: &ReceiveContext,
-    host: &Host<State>,
+// The user's account on the platform named in an eligibility check, if any
+fn requested_platform_account(
+    user: &UserCompliance,
+    params: &CheckEligibilityParams,
+) -> Option<ScopedAccount> {
+    let platform_id = params.platform_id.as_ref()?;
+    user.platform_accounts.get(platform_id).map(|account| account.clone())
+}
+
+// The user's account on the game category named in an eligibility check, if any
+fn requested_category_account(
+    user: &UserCompliance,
+    params: &CheckEligibilityParams,
+) -> Option<ScopedAccount> {
+    let category = params.category.as_ref()?;
+    user.category_accounts.get(category).map(|account| account.clone())
+}
+
+// Eligibility engine: work out whether a user may place a bet of the proposed
+// amount. record_transaction and check_eligibility both go through here, so
+// they always agree: a bet is recorded exactly when this returns Eligible. It
+// never changes state; counters from a period that is due for a reset are
+// treated as already reset.
+// The checks run from the strongest block to the weakest: pause, registration,
+// age, exclusions, then limits.
+fn evaluate_bet(
+    state: &State,
+    params: &CheckEligibilityParams,
+    current_time: Timestamp,
 ) -> Result<EligibilityStatus, ContractError> {
-    let params: CheckEligibilityParams = ctx.parameter_cursor().get()?;
-    let user_hash = hash_account(params.user_account);
-    let current_time = ctx.metadata().slot_time();
+    let user_hash = &hash_account(params.user_account);
+    let proposed_amount = params.proposed_amount;
+    
+    // No bet is recorded while transactions are paused
+    if state.paused.transactions {
+        return Ok(EligibilityStatus::TransactionsPaused);
+    }
     
-    let user = match host.state().registry.get(&user_hash) {
+    let user = match state.registry.get(user_hash) {
         Some(u) => u,
         None => return Ok(EligibilityStatus::NotRegistered),
     };
@@ -429,7 +4039,7 @@ fn check_eligibility(
     }
     
     // Check if user is excluded
-    if host.state().excluded_users.contains(&user_hash) {
+    if state.excluded_users.contains(user_hash) {
         if let Some(cooldown_until) = user.cooldown_until {
             if current_time < cooldown_until {
                 return Ok(EligibilityStatus::OnCooldown);
@@ -439,32 +4049,272 @@ fn check_eligibility(
         }
     }
     
-    // Calculate adjusted spending
-    let mut daily_spent = user.daily_spent;
-    let mut monthly_spent = user.monthly_spent;
+    // Check whether the user has come back from a self-exclusion
+    if awaiting_reactivation(&user, current_time) {
+        return Ok(EligibilityStatus::PendingReactivation);
+    }
     
-    let time_since_daily = current_time.duration_since(user.last_reset_day);
-    if let Some(duration) = time_since_daily {
-        if duration.days() >= 1 {
-            daily_spent = Amount::zero();
+    // Check if user is excluded from the requested platform
+    if let Some(platform_id) = &params.platform_id {
+        if active_exclusion(&user.platform_exclusions, platform_id, current_time).is_some() {
+            return Ok(EligibilityStatus::PlatformExcluded);
         }
     }
     
-    let time_since_monthly = current_time.duration_since(user.last_reset_month);
-    if let Some(duration) = time_since_monthly {
-        if duration.days() >= 30 {
-            monthly_spent = Amount::zero();
+    // Check if user is excluded from the requested game category
+    if let Some(category) = &params.category {
+        if active_exclusion(&user.category_exclusions, category, current_time).is_some() {
+            return Ok(EligibilityStatus::CategoryExcluded);
         }
     }
     
+    // Check per-bet maximum
+    if let Some(max_stake) = effective_max_stake(user.max_stake, state.stake_cap) {
+        if proposed_amount > max_stake {
+            return Ok(EligibilityStatus::StakeTooHigh);
+        }
+    }
+    
+    // Calculate adjusted spending
+    let (daily_spent, monthly_spent) = current_spending(&user, current_time);
+    
     // Check limits
-    if daily_spent.micro_ccd + params.proposed_amount.micro_ccd > user.daily_limit.micro_ccd {
+    if exceeds_limit(daily_spent, proposed_amount, user.daily_limit) {
         return Ok(EligibilityStatus::DailyLimitReached);
     }
     
-    if monthly_spent.micro_ccd + params.proposed_amount.micro_ccd > user.monthly_limit.micro_ccd {
+    if exceeds_limit(monthly_spent, proposed_amount, user.monthly_limit) {
         return Ok(EligibilityStatus::MonthlyLimitReached);
     }
     
+    // Check limits on the requested platform
+    if let Some(account) = requested_platform_account(&user, params) {
+        match scoped_limit_exceeded(&user, &account, proposed_amount, current_time) {
+            Some(ScopedLimit::Daily) => return Ok(EligibilityStatus::PlatformDailyLimitReached),
+            Some(ScopedLimit::Monthly) => return Ok(EligibilityStatus::PlatformMonthlyLimitReached),
+            None => {}
+        }
+    }
+    
+    // Check limits on the requested game category
+    if let Some(account) = requested_category_account(&user, params) {
+        match scoped_limit_exceeded(&user, &account, proposed_amount, current_time) {
+            Some(ScopedLimit::Daily) => return Ok(EligibilityStatus::CategoryDailyLimitReached),
+            Some(ScopedLimit::Monthly) => return Ok(EligibilityStatus::CategoryMonthlyLimitReached),
+            None => {}
+        }
+    }
+    
+    // Check the affordability threshold
+    let headroom = affordability_headroom(&user, state.affordability_threshold, current_time);
+    if headroom.is_some_and(|headroom| proposed_amount > headroom) {
+        return Ok(EligibilityStatus::AffordabilityCheckRequired);
+    }
+    
+    // Check bet count limits
+    let (hourly_bets, daily_bets) = current_bet_counts(&user, current_time);
+    if bet_count_reached(&user, hourly_bets, daily_bets) {
+        return Ok(EligibilityStatus::BetCountLimitReached);
+    }
+    
+    // Check daily play time
+    if session_time_reached(&user, current_time) {
+        return Ok(EligibilityStatus::SessionTimeLimitReached);
+    }
+    
+    Ok(EligibilityStatus::Eligible)
+}
+
+// Eligibility as reported by check_eligibility and check_eligibility_batch:
+// the engine's answer, plus an advisory review once the user's risk score
+// reaches the review threshold. ReviewRequired does not block a bet, so a
+// bet is recorded exactly when this returns Eligible or ReviewRequired.
+fn evaluate_eligibility(
+    state: &State,
+    params: &CheckEligibilityParams,
+    current_time: Timestamp,
+) -> Result<EligibilityStatus, ContractError> {
+    let status = evaluate_bet(state, params, current_time)?;
+    if status != EligibilityStatus::Eligible {
+        return Ok(status);
+    }
+    
+    // Check whether the user's betting pattern needs a review
+    let review_required = state.risk_review_threshold.is_some_and(|threshold| {
+        state
+            .registry
+            .get(&hash_account(params.user_account))
+            .is_some_and(|user| user.risk.score >= threshold)
+    });
+    if review_required {
+        return Ok(EligibilityStatus::ReviewRequired);
+    }
+    
     Ok(EligibilityStatus::Eligible)
 }
+
+// Eligibility status together with the remaining allowance and, when the user
+// is blocked, the time at which the blocking condition ends.
+fn eligibility_response(
+    state: &State,
+    params: &CheckEligibilityParams,
+    current_time: Timestamp,
+) -> Result<EligibilityResponse, ContractError> {
+    let status = evaluate_eligibility(state, params, current_time)?;
+    
+    let user = match state.registry.get(&hash_account(params.user_account)) {
+        Some(u) => u,
+        None => {
+            return Ok(EligibilityResponse {
+                status,
+                remaining_daily: Amount::zero(),
+                remaining_monthly: Amount::zero(),
+                max_eligible_stake: Amount::zero(),
+                blocked_until: None,
+                exclusion: None,
+            })
+        }
+    };
+    
+    let (daily_spent, monthly_spent) = current_spending(&user, current_time);
+    let remaining_daily = remaining_under(user.daily_limit, daily_spent);
+    let remaining_monthly = remaining_under(user.monthly_limit, monthly_spent);
+    
+    // Allowance left under the global limits and the requested platform's and
+    // category's limits
+    let mut allowance = remaining_daily.min(remaining_monthly);
+    if let Some(account) = requested_platform_account(&user, params) {
+        allowance = scoped_allowance(&user, &account, allowance, current_time);
+    }
+    if let Some(account) = requested_category_account(&user, params) {
+        allowance = scoped_allowance(&user, &account, allowance, current_time);
+    }
+    if let Some(headroom) = affordability_headroom(&user, state.affordability_threshold, current_time)
+    {
+        allowance = allowance.min(headroom);
+    }
+    
+    // Only spending and stake limits leave room for a smaller bet. A review is
+    // advisory, so the bet is still recorded and the allowance stands.
+    let max_eligible_stake = match status {
+        EligibilityStatus::Eligible
+        | EligibilityStatus::DailyLimitReached
+        | EligibilityStatus::MonthlyLimitReached
+        | EligibilityStatus::PlatformDailyLimitReached
+        | EligibilityStatus::PlatformMonthlyLimitReached
+        | EligibilityStatus::CategoryDailyLimitReached
+        | EligibilityStatus::CategoryMonthlyLimitReached
+        | EligibilityStatus::AffordabilityCheckRequired
+        | EligibilityStatus::StakeTooHigh
+        | EligibilityStatus::ReviewRequired => {
+            match effective_max_stake(user.max_stake, state.stake_cap) {
+                Some(max_stake) => allowance.min(max_stake),
+                None => allowance,
+            }
+        }
+        _ => Amount::zero(),
+    };
+    
+    // Platform or category exclusion the user is blocked by
+    let scoped_exclusion = match status {
+        EligibilityStatus::PlatformExcluded => params
+            .platform_id
+            .as_ref()
+            .and_then(|platform_id| {
+                active_exclusion(&user.platform_exclusions, platform_id, current_time)
+            }),
+        EligibilityStatus::CategoryExcluded => params
+            .category
+            .as_ref()
+            .and_then(|category| active_exclusion(&user.category_exclusions, category, current_time)),
+        _ => None,
+    };
+    
+    let exclusion = match status {
+        EligibilityStatus::SelfExcluded | EligibilityStatus::OnCooldown => user.exclusion_source,
+        _ => scoped_exclusion.map(|exclusion| exclusion.source),
+    };
+    
+    let blocked_until = match status {
+        EligibilityStatus::OnCooldown => user.cooldown_until,
+        EligibilityStatus::PendingReactivation => reactivation_time(&user),
+        EligibilityStatus::PlatformExcluded | EligibilityStatus::CategoryExcluded => {
+            scoped_exclusion.map(|exclusion| exclusion.until)
+        }
+        EligibilityStatus::DailyLimitReached
+        | EligibilityStatus::PlatformDailyLimitReached
+        | EligibilityStatus::CategoryDailyLimitReached
+        | EligibilityStatus::SessionTimeLimitReached => {
+            user.last_reset_day.checked_add(Duration::from_days(1))
+        }
+        EligibilityStatus::MonthlyLimitReached
+        | EligibilityStatus::PlatformMonthlyLimitReached
+        | EligibilityStatus::CategoryMonthlyLimitReached => {
+            user.last_reset_month.checked_add(Duration::from_days(30))
+        }
+        EligibilityStatus::BetCountLimitReached => {
+            // The daily count only clears at the next daily reset
+            let (_, daily_bets) = current_bet_counts(&user, current_time);
+            if user.max_bets_per_day.is_some_and(|max| daily_bets >= max) {
+                user.last_reset_day.checked_add(Duration::from_days(1))
+            } else {
+                user.last_reset_hour.checked_add(Duration::from_hours(1))
+            }
+        }
+        _ => None,
+    };
+    
+    Ok(EligibilityResponse {
+        status,
+        remaining_daily,
+        remaining_monthly,
+        max_eligible_stake,
+        blocked_until,
+        exclusion,
+    })
+}
+
+// Check if a user is eligible to place a bet 
+#[receive(
+    contract = "safestake_registry",
+    name = "check_eligibility",
+    parameter = "CheckEligibilityParams",
+    return_value = "EligibilityResponse",
+    error = "ContractError"
+)]
+fn check_eligibility(
+    ctx: &ReceiveContext,
+    host: &Host<State>,
+) -> Result<EligibilityResponse, ContractError> {
+    let params: CheckEligibilityParams = ctx.parameter_cursor().get()?;
+    let current_time = ctx.metadata().slot_time();
+    
+    eligibility_response(host.state(), &params, current_time)
+}
+
+// Check eligibility and remaining allowance for many users in one invoke
+#[receive(
+    contract = "safestake_registry",
+    name = "check_eligibility_batch",
+    parameter = "CheckEligibilityBatchParams",
+    return_value = "Vec<EligibilityBatchEntry>",
+    error = "ContractError"
+)]
+fn check_eligibility_batch(
+    ctx: &ReceiveContext,
+    host: &Host<State>,
+) -> Result<Vec<EligibilityBatchEntry>, ContractError> {
+    let params: CheckEligibilityBatchParams = ctx.parameter_cursor().get()?;
+    let current_time = ctx.metadata().slot_time();
+    
+    let mut results = Vec::with_capacity(params.checks.len());
+    for check in params.checks {
+        let eligibility = eligibility_response(host.state(), &check, current_time)?;
+        results.push(EligibilityBatchEntry {
+            user_account: check.user_account,
+            eligibility,
+        });
+    }
+    
+    Ok(results)
+}
@@ -5,15 +5,21 @@
 use concordium_smart_contract_testing::*;
 use safestake_registry::*;
 
-use concordium_std::{PublicKeyEd25519, SignatureEd25519};
+use concordium_std::{PublicKeyEd25519, Serial, SignatureEd25519};
+
+use std::collections::BTreeMap;
 
 // ed25519-dalek for signature generation
 use ed25519_dalek::{SigningKey, Signer as DalekSigner};
+use sha2::{Digest, Sha256};
+
+use proptest::prelude::*;
 
 // Test accounts
 const ALICE: AccountAddress = AccountAddress([1u8; 32]);
 const BOB: AccountAddress = AccountAddress([2u8; 32]);
 const CHARLIE: AccountAddress = AccountAddress([3u8; 32]);
+const DAVE: AccountAddress = AccountAddress([4u8; 32]);
 const ALICE_ADDR: Address = Address::Account(ALICE);
 const BOB_ADDR: Address = Address::Account(BOB);
 
@@ -57,6 +63,45 @@ fn sign_account_address(signing_key: &SigningKey, account: AccountAddress) -> Si
     SignatureEd25519(signature.to_bytes())
 }
 
+// Message the verifier signs to attest a user's affordability check
+fn attestation_message(
+    contract: ContractAddress,
+    account: AccountAddress,
+    nonce: u64,
+    valid_until: Timestamp,
+) -> Vec<u8> {
+    let mut message = b"SafeStake affordability attestation".to_vec();
+    message.extend_from_slice(&concordium_std::to_bytes(&contract));
+    message.extend_from_slice(&account.0);
+    message.extend_from_slice(&nonce.to_le_bytes());
+    message.extend_from_slice(&valid_until.timestamp_millis().to_le_bytes());
+    message
+}
+
+// Sign a permit message with a single account key, the way a wallet signs
+// messages: the SHA-256 hash of the account address, 8 zero bytes and the message
+fn sign_permit(
+    signing_key: &SigningKey,
+    signer: AccountAddress,
+    message: &PermitMessage,
+) -> concordium_std::AccountSignatures {
+    let mut data = signer.0.to_vec();
+    data.extend_from_slice(&[0u8; 8]);
+    data.extend_from_slice(&concordium_std::to_bytes(message));
+    let message_hash = Sha256::digest(&data);
+    let signature = signing_key.sign(&message_hash);
+    
+    let credential = concordium_std::CredentialSignatures {
+        sigs: BTreeMap::from([(
+            0,
+            concordium_std::Signature::Ed25519(SignatureEd25519(signature.to_bytes())),
+        )]),
+    };
+    concordium_std::AccountSignatures {
+        sigs: BTreeMap::from([(0, credential)]),
+    }
+}
+
 // Struct to hold test verifier credentials
 struct TestVerifier {
     public_key: PublicKeyEd25519,
@@ -85,14 +130,18 @@ impl TestVerifier {
 // ============================================================================
 
 fn initialize_with_verifier(verifier: &TestVerifier) -> (Chain, ContractInitSuccess) {
+    initialize_module(verifier, "./concordium-out/module.wasm.v1")
+}
+
+// Deploy the module at the given path and initialize the contract from it
+fn initialize_module(verifier: &TestVerifier, path: &str) -> (Chain, ContractInitSuccess) {
     let mut chain = Chain::new();
     
     chain.create_account(Account::new(ALICE, ACC_INITIAL_BALANCE));
     chain.create_account(Account::new(BOB, ACC_INITIAL_BALANCE));
     chain.create_account(Account::new(CHARLIE, ACC_INITIAL_BALANCE));
     
-    let module = module_load_v1("./concordium-out/module.wasm.v1")
-        .expect("Module exists at path");
+    let module = module_load_v1(path).expect("Module exists at path");
     let deployment = chain
         .module_deploy_v1(SIGNER, ALICE, module)
         .expect("Deploy valid module");
@@ -156,6 +205,18 @@ fn register_user_with_age_verification(
         .expect("Register user should succeed");
 }
 
+// Spending limits with every optional limit left unset
+fn limits_params(daily: u64, monthly: u64) -> SetLimitsParams {
+    SetLimitsParams {
+        daily_limit: Amount::from_micro_ccd(daily),
+        monthly_limit: Amount::from_micro_ccd(monthly),
+        max_stake: None,
+        max_bets_per_hour: None,
+        max_bets_per_day: None,
+        daily_time_limit: None,
+    }
+}
+
 fn set_limits(
     chain: &mut Chain,
     init: &ContractInitSuccess,
@@ -164,11 +225,16 @@ fn set_limits(
     daily: u64,
     monthly: u64,
 ) {
-    let params = SetLimitsParams {
-        daily_limit: Amount::from_micro_ccd(daily),
-        monthly_limit: Amount::from_micro_ccd(monthly),
-    };
-    
+    set_limits_with(chain, init, account, addr, limits_params(daily, monthly));
+}
+
+fn set_limits_with(
+    chain: &mut Chain,
+    init: &ContractInitSuccess,
+    account: AccountAddress,
+    addr: Address,
+    params: SetLimitsParams,
+) {
     chain
         .contract_update(
             SIGNER,
@@ -188,17 +254,69 @@ fn set_limits(
         .expect("Set limits should succeed");
 }
 
+fn set_platform_limits(
+    chain: &mut Chain,
+    init: &ContractInitSuccess,
+    account: AccountAddress,
+    platform_id: &str,
+    daily: Option<u64>,
+    monthly: Option<u64>,
+) {
+    let params = SetPlatformLimitsParams {
+        platform_id: platform_id.to_string(),
+        daily_limit: daily.map(Amount::from_micro_ccd),
+        monthly_limit: monthly.map(Amount::from_micro_ccd),
+    };
+    
+    chain
+        .contract_update(
+            SIGNER,
+            account,
+            Address::Account(account),
+            Energy::from(10_000),
+            UpdateContractPayload {
+                address: init.contract_address,
+                amount: Amount::zero(),
+                receive_name: OwnedReceiveName::new_unchecked(
+                    "safestake_registry.set_platform_limits".to_string()
+                ),
+                message: OwnedParameter::from_serial(&params)
+                    .expect("Parameter within size bounds"),
+            },
+        )
+        .expect("Set platform limits should succeed");
+}
+
 fn check_eligibility(
     chain: &Chain,
     init: &ContractInitSuccess,
     user_account: AccountAddress,
     proposed_amount: u64,
 ) -> EligibilityStatus {
+    check_eligibility_details(chain, init, user_account, proposed_amount).status
+}
+
+fn check_eligibility_details(
+    chain: &Chain,
+    init: &ContractInitSuccess,
+    user_account: AccountAddress,
+    proposed_amount: u64,
+) -> EligibilityResponse {
     let params = CheckEligibilityParams {
         user_account,
         proposed_amount: Amount::from_micro_ccd(proposed_amount),
+        platform_id: None,
+        category: None,
     };
     
+    check_eligibility_with(chain, init, &params)
+}
+
+fn check_eligibility_with(
+    chain: &Chain,
+    init: &ContractInitSuccess,
+    params: &CheckEligibilityParams,
+) -> EligibilityResponse {
     let invoke = chain
         .contract_invoke(
             ALICE,
@@ -210,7 +328,7 @@ fn check_eligibility(
                 receive_name: OwnedReceiveName::new_unchecked(
                     "safestake_registry.check_eligibility".to_string()
                 ),
-                message: OwnedParameter::from_serial(&params)
+                message: OwnedParameter::from_serial(params)
                     .expect("Parameter within size bounds"),
             },
         )
@@ -218,7 +336,21 @@ fn check_eligibility(
     
     invoke
         .parse_return_value()
-        .expect("Should return EligibilityStatus")
+        .expect("Should return EligibilityResponse")
+}
+
+// The user's own platform salt, created by their wallet and never sent to the contract
+fn user_salt(user_account: AccountAddress) -> [u8; 32] {
+    let mut data = b"safestake platform salt".to_vec();
+    data.extend_from_slice(&user_account.0);
+    Sha256::digest(&data).into()
+}
+
+// Commitment to a platform as the user's wallet computes it for the operator
+fn platform_commitment(user_account: AccountAddress, platform_id: &str) -> [u8; 32] {
+    let mut data = user_salt(user_account).to_vec();
+    data.extend_from_slice(platform_id.as_bytes());
+    Sha256::digest(&data).into()
 }
 
 fn record_transaction(
@@ -231,12 +363,263 @@ fn record_transaction(
     let params = RecordTransactionParams {
         user_account,
         amount: Amount::from_micro_ccd(amount),
-        platform_id: platform_id.to_string(),
+        platform_commitment: platform_commitment(user_account, platform_id),
+        category: GameCategory::Sports,
+    };
+    let operator = platform_operator(chain, init, platform_id);
+    
+    let update = chain
+        .contract_update(
+            SIGNER,
+            operator,
+            Address::Account(operator),
+            Energy::from(10_000),
+            UpdateContractPayload {
+                address: init.contract_address,
+                amount: Amount::zero(),
+                receive_name: OwnedReceiveName::new_unchecked(
+                    "safestake_registry.record_transaction".to_string()
+                ),
+                message: OwnedParameter::from_serial(&params)
+                    .expect("Parameter within size bounds"),
+            },
+        )
+        .expect("Record transaction should succeed");
+    
+    let outcome: TransactionOutcome = update
+        .parse_return_value()
+        .expect("Should return TransactionOutcome");
+    assert_eq!(outcome, TransactionOutcome::Recorded);
+}
+
+fn try_record_transaction(
+    chain: &mut Chain,
+    init: &ContractInitSuccess,
+    user_account: AccountAddress,
+    amount: u64,
+    platform_id: &str,
+) -> TransactionOutcome {
+    try_record_category_transaction(
+        chain,
+        init,
+        user_account,
+        amount,
+        platform_id,
+        GameCategory::Sports,
+    )
+}
+
+fn try_record_category_transaction(
+    chain: &mut Chain,
+    init: &ContractInitSuccess,
+    user_account: AccountAddress,
+    amount: u64,
+    platform_id: &str,
+    category: GameCategory,
+) -> TransactionOutcome {
+    let params = RecordTransactionParams {
+        user_account,
+        amount: Amount::from_micro_ccd(amount),
+        platform_commitment: platform_commitment(user_account, platform_id),
+        category,
     };
+    let operator = platform_operator(chain, init, platform_id);
     
     chain
         .contract_update(
             SIGNER,
+            operator,
+            Address::Account(operator),
+            Energy::from(10_000),
+            UpdateContractPayload {
+                address: init.contract_address,
+                amount: Amount::zero(),
+                receive_name: OwnedReceiveName::new_unchecked(
+                    "safestake_registry.record_transaction".to_string()
+                ),
+                message: OwnedParameter::from_serial(&params)
+                    .expect("Parameter within size bounds"),
+            },
+        )
+        .expect("Record transaction should succeed")
+        .parse_return_value()
+        .expect("Should return TransactionOutcome")
+}
+
+fn set_stake_cap(
+    chain: &mut Chain,
+    init: &ContractInitSuccess,
+    sender: AccountAddress,
+    stake_cap: Option<u64>,
+) -> Result<ContractInvokeSuccess, ContractInvokeError> {
+    let params = SetStakeCapParams {
+        stake_cap: stake_cap.map(Amount::from_micro_ccd),
+    };
+    
+    chain.contract_update(
+        SIGNER,
+        sender,
+        Address::Account(sender),
+        Energy::from(10_000),
+        UpdateContractPayload {
+            address: init.contract_address,
+            amount: Amount::zero(),
+            receive_name: OwnedReceiveName::new_unchecked(
+                "safestake_registry.set_stake_cap".to_string()
+            ),
+            message: OwnedParameter::from_serial(&params)
+                .expect("Parameter within size bounds"),
+        },
+    )
+}
+
+// Account of the operator running a platform, registered by the admin on
+// first use
+fn platform_operator(
+    chain: &mut Chain,
+    init: &ContractInitSuccess,
+    platform_id: &str,
+) -> AccountAddress {
+    let operator = AccountAddress(Sha256::digest(platform_id.as_bytes()).into());
+    if chain.account_balance(operator).is_none() {
+        chain.create_account(Account::new(operator, ACC_INITIAL_BALANCE));
+        let params = SetOperatorParams {
+            operator,
+            platform_id: Some(platform_id.to_string()),
+        };
+        update_as(chain, init, ALICE, "set_operator", &params)
+            .expect("Admin should register the operator");
+    }
+    operator
+}
+
+// Start or end a session as the platform's operator
+fn update_session(
+    chain: &mut Chain,
+    init: &ContractInitSuccess,
+    entrypoint: &str,
+    user_account: AccountAddress,
+    platform_id: &str,
+) -> Result<ContractInvokeSuccess, ContractInvokeError> {
+    let params = SessionParams {
+        user_account,
+        platform_id: platform_id.to_string(),
+    };
+    let operator = platform_operator(chain, init, platform_id);
+    
+    chain.contract_update(
+        SIGNER,
+        operator,
+        Address::Account(operator),
+        Energy::from(10_000),
+        UpdateContractPayload {
+            address: init.contract_address,
+            amount: Amount::zero(),
+            receive_name: OwnedReceiveName::new_unchecked(format!(
+                "safestake_registry.{}",
+                entrypoint
+            )),
+            message: OwnedParameter::from_serial(&params)
+                .expect("Parameter within size bounds"),
+        },
+    )
+}
+
+// Call any entrypoint as the given account
+fn update_as<P: Serial>(
+    chain: &mut Chain,
+    init: &ContractInitSuccess,
+    sender: AccountAddress,
+    entrypoint: &str,
+    params: &P,
+) -> Result<ContractInvokeSuccess, ContractInvokeError> {
+    chain.contract_update(
+        SIGNER,
+        sender,
+        Address::Account(sender),
+        Energy::from(10_000),
+        UpdateContractPayload {
+            address: init.contract_address,
+            amount: Amount::zero(),
+            receive_name: OwnedReceiveName::new_unchecked(format!(
+                "safestake_registry.{}",
+                entrypoint
+            )),
+            message: OwnedParameter::from_serial(params)
+                .expect("Parameter within size bounds"),
+        },
+    )
+}
+
+// Convert up to max_users users left behind by an upgrade, returning whether
+// any are still waiting
+fn migrate_users(chain: &mut Chain, init: &ContractInitSuccess, max_users: u32) -> bool {
+    update_as(chain, init, CHARLIE, "migrate_users", &max_users)
+        .expect("Migrating users should succeed")
+        .parse_return_value()
+        .expect("Should return bool")
+}
+
+// Upgrade a contract to the current module and migrate its state and users
+fn upgrade_to_current(chain: &mut Chain, init: &ContractInitSuccess) {
+    let module = module_load_v1("./concordium-out/module.wasm.v1").expect("Module exists at path");
+    let deployment = chain
+        .module_deploy_v1(SIGNER, ALICE, module)
+        .expect("Deploy valid module");
+    let params = UpgradeParams {
+        module: deployment.module_reference,
+        migrate: Some((
+            OwnedEntrypointName::new_unchecked("migrate".to_string()),
+            OwnedParameter::empty(),
+        )),
+    };
+    update_as(chain, init, ALICE, "upgrade", &params).expect("Admin should upgrade");
+    assert!(!migrate_users(chain, init, 100));
+}
+
+// Call any entrypoint from a contract, in a transaction sent by the invoker
+fn update_from_contract<P: Serial>(
+    chain: &mut Chain,
+    init: &ContractInitSuccess,
+    invoker: AccountAddress,
+    sender: ContractAddress,
+    entrypoint: &str,
+    params: &P,
+) -> Result<ContractInvokeSuccess, ContractInvokeError> {
+    chain.contract_update(
+        SIGNER,
+        invoker,
+        Address::Contract(sender),
+        Energy::from(10_000),
+        UpdateContractPayload {
+            address: init.contract_address,
+            amount: Amount::zero(),
+            receive_name: OwnedReceiveName::new_unchecked(format!(
+                "safestake_registry.{}",
+                entrypoint
+            )),
+            message: OwnedParameter::from_serial(params)
+                .expect("Parameter within size bounds"),
+        },
+    )
+}
+
+// Events logged by the contract during an update
+fn contract_events(update: &ContractInvokeSuccess) -> Vec<Event> {
+    update
+        .events()
+        .flat_map(|(_, events)| events.iter())
+        .map(|event| event.parse().expect("Should parse Event"))
+        .collect()
+}
+
+fn get_play_time(
+    chain: &Chain,
+    init: &ContractInitSuccess,
+    user_account: AccountAddress,
+) -> PlayTimeInfo {
+    let invoke = chain
+        .contract_invoke(
             ALICE,
             ALICE_ADDR,
             Energy::from(10_000),
@@ -244,13 +627,78 @@ fn record_transaction(
                 address: init.contract_address,
                 amount: Amount::zero(),
                 receive_name: OwnedReceiveName::new_unchecked(
-                    "safestake_registry.record_transaction".to_string()
+                    "safestake_registry.get_play_time".to_string()
+                ),
+                message: OwnedParameter::from_serial(&user_account)
+                    .expect("Parameter within size bounds"),
+            },
+        )
+        .expect("Invoke should succeed");
+    
+    invoke
+        .parse_return_value()
+        .expect("Should return PlayTimeInfo")
+}
+
+fn record_transactions_batch(
+    chain: &mut Chain,
+    init: &ContractInitSuccess,
+    platform_id: &str,
+    entries: Vec<BatchTransactionEntry>,
+) -> Vec<BatchTransactionResult> {
+    let params = RecordTransactionsBatchParams { entries };
+    let operator = platform_operator(chain, init, platform_id);
+    
+    let update = chain
+        .contract_update(
+            SIGNER,
+            operator,
+            Address::Account(operator),
+            Energy::from(50_000),
+            UpdateContractPayload {
+                address: init.contract_address,
+                amount: Amount::zero(),
+                receive_name: OwnedReceiveName::new_unchecked(
+                    "safestake_registry.record_transactions_batch".to_string()
                 ),
                 message: OwnedParameter::from_serial(&params)
                     .expect("Parameter within size bounds"),
             },
         )
-        .expect("Record transaction should succeed");
+        .expect("Batch should succeed");
+    
+    update
+        .parse_return_value()
+        .expect("Should return batch results")
+}
+
+fn check_eligibility_batch(
+    chain: &Chain,
+    init: &ContractInitSuccess,
+    checks: Vec<CheckEligibilityParams>,
+) -> Vec<EligibilityBatchEntry> {
+    let params = CheckEligibilityBatchParams { checks };
+    
+    let invoke = chain
+        .contract_invoke(
+            ALICE,
+            ALICE_ADDR,
+            Energy::from(50_000),
+            UpdateContractPayload {
+                address: init.contract_address,
+                amount: Amount::zero(),
+                receive_name: OwnedReceiveName::new_unchecked(
+                    "safestake_registry.check_eligibility_batch".to_string()
+                ),
+                message: OwnedParameter::from_serial(&params)
+                    .expect("Parameter within size bounds"),
+            },
+        )
+        .expect("Invoke should succeed");
+    
+    invoke
+        .parse_return_value()
+        .expect("Should return batch eligibility")
 }
 
 // ============================================================================
@@ -486,14 +934,16 @@ fn test_exceed_daily_limit() {
     let params = RecordTransactionParams {
         user_account: ALICE,
         amount: Amount::from_micro_ccd(500_000_000),
-        platform_id: "platform_1".to_string(),
+        platform_commitment: platform_commitment(ALICE, "platform_1"),
+        category: GameCategory::Sports,
     };
+    let operator = platform_operator(&mut chain, &init, "platform_1");
     
-    let result = chain
+    let outcome: TransactionOutcome = chain
         .contract_update(
             SIGNER,
-            ALICE,
-            ALICE_ADDR,
+            operator,
+            Address::Account(operator),
             Energy::from(10_000),
             UpdateContractPayload {
                 address: init.contract_address,
@@ -505,12 +955,10 @@ fn test_exceed_daily_limit() {
                     .expect("Parameter within size bounds"),
             },
         )
-        .expect_err("Should fail - daily limit exceeded");
-    
-    let error: ContractError = result
+        .expect("Record transaction should succeed")
         .parse_return_value()
-        .expect("Should return ContractError");
-    assert_eq!(error, ContractError::DailyLimitExceeded);
+        .expect("Should return TransactionOutcome");
+    assert_eq!(outcome, TransactionOutcome::DailyLimitExceeded);
 }
 
 // Test 10: Check eligibility shows daily limit reached
@@ -544,14 +992,16 @@ fn test_no_age_verification_blocks_transactions() {
     let params = RecordTransactionParams {
         user_account: ALICE,
         amount: Amount::from_micro_ccd(500_000_000),
-        platform_id: "platform_1".to_string(),
+        platform_commitment: platform_commitment(ALICE, "platform_1"),
+        category: GameCategory::Sports,
     };
+    let operator = platform_operator(&mut chain, &init, "platform_1");
     
-    let result = chain
+    let outcome: TransactionOutcome = chain
         .contract_update(
             SIGNER,
-            ALICE,
-            ALICE_ADDR,
+            operator,
+            Address::Account(operator),
             Energy::from(10_000),
             UpdateContractPayload {
                 address: init.contract_address,
@@ -563,12 +1013,10 @@ fn test_no_age_verification_blocks_transactions() {
                     .expect("Parameter within size bounds"),
             },
         )
-        .expect_err("Should fail - age not verified");
-    
-    let error: ContractError = result
+        .expect("Record transaction should succeed")
         .parse_return_value()
-        .expect("Should return ContractError");
-    assert_eq!(error, ContractError::AgeNotVerified);
+        .expect("Should return TransactionOutcome");
+    assert_eq!(outcome, TransactionOutcome::AgeNotVerified);
 }
 
 // Test 12: User without age verification shows in eligibility check
@@ -642,14 +1090,16 @@ fn test_unregistered_user_cannot_transact() {
     let params = RecordTransactionParams {
         user_account: CHARLIE,
         amount: Amount::from_micro_ccd(500_000_000),
-        platform_id: "platform_1".to_string(),
+        platform_commitment: platform_commitment(CHARLIE, "platform_1"),
+        category: GameCategory::Sports,
     };
+    let operator = platform_operator(&mut chain, &init, "platform_1");
     
-    let result = chain
+    let outcome: TransactionOutcome = chain
         .contract_update(
             SIGNER,
-            ALICE,
-            ALICE_ADDR,
+            operator,
+            Address::Account(operator),
             Energy::from(10_000),
             UpdateContractPayload {
                 address: init.contract_address,
@@ -661,12 +1111,10 @@ fn test_unregistered_user_cannot_transact() {
                     .expect("Parameter within size bounds"),
             },
         )
-        .expect_err("Should fail - user not registered");
-    
-    let error: ContractError = result
+        .expect("Record transaction should succeed")
         .parse_return_value()
-        .expect("Should return ContractError");
-    assert_eq!(error, ContractError::UserNotRegistered);
+        .expect("Should return TransactionOutcome");
+    assert_eq!(outcome, TransactionOutcome::NotRegistered);
 }
 
 // Test 16: Invalid limits rejected
@@ -675,10 +1123,7 @@ fn test_invalid_limits_rejected() {
     let verifier = TestVerifier::new();
     let (mut chain, init) = initialize_with_verifier(&verifier);
     
-    let params = SetLimitsParams {
-        daily_limit: Amount::from_micro_ccd(10_000_000_000),
-        monthly_limit: Amount::from_micro_ccd(5_000_000_000),
-    };
+    let params = limits_params(10_000_000_000, 5_000_000_000);
     
     let result = chain
         .contract_update(
@@ -743,4 +1188,3258 @@ fn test_complete_user_journey() {
     
     println!("\n=== USER JOURNEY COMPLETE ===");
     println!("✅ All responsible gambling features working!\n");
-}
\ No newline at end of file
+}
+
+// ============================================================================
+// TESTS - BATCH OPERATIONS
+// ============================================================================
+
+// Test 18: Batch returns a result per entry and keeps the accepted bets
+#[test]
+fn test_record_transactions_batch_partial_failure() {
+    let verifier = TestVerifier::new();
+    let (mut chain, init) = initialize_with_verifier(&verifier);
+    
+    // ALICE is registered with 1 CCD/day, BOB only set limits (no age check)
+    register_user_with_age_verification(&mut chain, &init, ALICE, ALICE_ADDR, &verifier);
+    set_limits(&mut chain, &init, ALICE, ALICE_ADDR, 1_000_000_000, 5_000_000_000);
+    set_limits(&mut chain, &init, BOB, BOB_ADDR, 1_000_000_000, 5_000_000_000);
+    
+    let entries = vec![
+        BatchTransactionEntry {
+            user_account: ALICE,
+            amount: Amount::from_micro_ccd(600_000_000),
+            platform_commitment: platform_commitment(ALICE, "casino"),
+            category: GameCategory::Sports,
+            bet_id: 1,
+        },
+        BatchTransactionEntry {
+            user_account: ALICE,
+            amount: Amount::from_micro_ccd(500_000_000),
+            platform_commitment: platform_commitment(ALICE, "casino"),
+            category: GameCategory::Sports,
+            bet_id: 2,
+        },
+        BatchTransactionEntry {
+            user_account: BOB,
+            amount: Amount::from_micro_ccd(100_000_000),
+            platform_commitment: platform_commitment(BOB, "casino"),
+            category: GameCategory::Sports,
+            bet_id: 3,
+        },
+        BatchTransactionEntry {
+            user_account: CHARLIE,
+            amount: Amount::from_micro_ccd(100_000_000),
+            platform_commitment: platform_commitment(CHARLIE, "casino"),
+            category: GameCategory::Sports,
+            bet_id: 4,
+        },
+        BatchTransactionEntry {
+            user_account: ALICE,
+            amount: Amount::from_micro_ccd(400_000_000),
+            platform_commitment: platform_commitment(ALICE, "casino"),
+            category: GameCategory::Sports,
+            bet_id: 5,
+        },
+    ];
+    
+    let results = record_transactions_batch(&mut chain, &init, "casino", entries);
+    
+    let outcomes: Vec<(u64, TransactionOutcome)> =
+        results.into_iter().map(|r| (r.bet_id, r.outcome)).collect();
+    assert_eq!(
+        outcomes,
+        vec![
+            (1, TransactionOutcome::Recorded),
+            (2, TransactionOutcome::DailyLimitExceeded),
+            (3, TransactionOutcome::AgeNotVerified),
+            (4, TransactionOutcome::NotRegistered),
+            (5, TransactionOutcome::Recorded),
+        ]
+    );
+    
+    // Accepted bets (0.6 + 0.4 CCD) used up the whole daily limit
+    let status = check_eligibility(&chain, &init, ALICE, 1);
+    assert_eq!(status, EligibilityStatus::DailyLimitReached);
+}
+
+// Test 19: Excluded users are reported per entry in a batch
+#[test]
+fn test_record_transactions_batch_excluded_user() {
+    let verifier = TestVerifier::new();
+    let (mut chain, init) = initialize_with_verifier(&verifier);
+    
+    register_user_with_age_verification(&mut chain, &init, ALICE, ALICE_ADDR, &verifier);
+    set_limits(&mut chain, &init, ALICE, ALICE_ADDR, 1_000_000_000, 5_000_000_000);
+    
+    chain
+        .contract_update(
+            SIGNER,
+            ALICE,
+            ALICE_ADDR,
+            Energy::from(10_000),
+            UpdateContractPayload {
+                address: init.contract_address,
+                amount: Amount::zero(),
+                receive_name: OwnedReceiveName::new_unchecked(
+                    "safestake_registry.self_exclude".to_string()
+                ),
+                message: OwnedParameter::from_serial(&SelfExcludeParams { duration_days: 7 })
+                    .expect("Parameter within size bounds"),
+            },
+        )
+        .expect("Self-exclusion should succeed");
+    
+    let results = record_transactions_batch(
+        &mut chain,
+        &init,
+        "casino",
+        vec![BatchTransactionEntry {
+            user_account: ALICE,
+            amount: Amount::from_micro_ccd(100_000_000),
+            platform_commitment: platform_commitment(ALICE, "casino"),
+            category: GameCategory::Sports,
+            bet_id: 42,
+        }],
+    );
+    
+    assert_eq!(
+        results,
+        vec![BatchTransactionResult {
+            bet_id: 42,
+            outcome: TransactionOutcome::OnCooldown,
+        }]
+    );
+}
+
+// Test 20: Batch eligibility reports status and remaining allowance per user
+#[test]
+fn test_check_eligibility_batch() {
+    let verifier = TestVerifier::new();
+    let (mut chain, init) = initialize_with_verifier(&verifier);
+    
+    register_user_with_age_verification(&mut chain, &init, ALICE, ALICE_ADDR, &verifier);
+    set_limits(&mut chain, &init, ALICE, ALICE_ADDR, 1_000_000_000, 5_000_000_000);
+    set_limits(&mut chain, &init, BOB, BOB_ADDR, 1_000_000_000, 5_000_000_000);
+    record_transaction(&mut chain, &init, ALICE, 300_000_000, "platform_1");
+    
+    let checks = [ALICE, BOB, CHARLIE]
+        .into_iter()
+        .map(|user_account| CheckEligibilityParams {
+            user_account,
+            proposed_amount: Amount::from_micro_ccd(500_000_000),
+            platform_id: None,
+            category: None,
+        })
+        .collect();
+    
+    let results = check_eligibility_batch(&chain, &init, checks);
+    
+    let summary: Vec<(AccountAddress, EligibilityStatus, Amount, Amount)> = results
+        .into_iter()
+        .map(|entry| {
+            (
+                entry.user_account,
+                entry.eligibility.status,
+                entry.eligibility.remaining_daily,
+                entry.eligibility.remaining_monthly,
+            )
+        })
+        .collect();
+    
+    assert_eq!(
+        summary,
+        vec![
+            (
+                ALICE,
+                EligibilityStatus::Eligible,
+                Amount::from_micro_ccd(700_000_000),
+                Amount::from_micro_ccd(4_700_000_000),
+            ),
+            (
+                BOB,
+                EligibilityStatus::AgeNotVerified,
+                Amount::from_micro_ccd(1_000_000_000),
+                Amount::from_micro_ccd(5_000_000_000),
+            ),
+            (CHARLIE, EligibilityStatus::NotRegistered, Amount::zero(), Amount::zero()),
+        ]
+    );
+}
+
+// ============================================================================
+// TESTS - ELIGIBILITY DETAILS
+// ============================================================================
+
+// Test 21: Eligibility response explains remaining allowance and next reset
+#[test]
+fn test_eligibility_details_daily_limit() {
+    let verifier = TestVerifier::new();
+    let (mut chain, init) = initialize_with_verifier(&verifier);
+    
+    register_user_with_age_verification(&mut chain, &init, ALICE, ALICE_ADDR, &verifier);
+    set_limits(&mut chain, &init, ALICE, ALICE_ADDR, 1_000_000_000, 5_000_000_000);
+    record_transaction(&mut chain, &init, ALICE, 600_000_000, "platform_1");
+    
+    let details = check_eligibility_details(&chain, &init, ALICE, 500_000_000);
+    
+    assert_eq!(details.status, EligibilityStatus::DailyLimitReached);
+    assert_eq!(details.remaining_daily, Amount::from_micro_ccd(400_000_000));
+    assert_eq!(details.remaining_monthly, Amount::from_micro_ccd(4_400_000_000));
+    assert_eq!(details.max_eligible_stake, Amount::from_micro_ccd(400_000_000));
+    assert_eq!(
+        details.blocked_until,
+        chain.block_time().checked_add(Duration::from_days(1))
+    );
+}
+
+// Test 22: Eligibility response reports when a cooldown ends
+#[test]
+fn test_eligibility_details_cooldown() {
+    let verifier = TestVerifier::new();
+    let (mut chain, init) = initialize_with_verifier(&verifier);
+    
+    register_user_with_age_verification(&mut chain, &init, ALICE, ALICE_ADDR, &verifier);
+    set_limits(&mut chain, &init, ALICE, ALICE_ADDR, 1_000_000_000, 5_000_000_000);
+    
+    chain
+        .contract_update(
+            SIGNER,
+            ALICE,
+            ALICE_ADDR,
+            Energy::from(10_000),
+            UpdateContractPayload {
+                address: init.contract_address,
+                amount: Amount::zero(),
+                receive_name: OwnedReceiveName::new_unchecked(
+                    "safestake_registry.self_exclude".to_string()
+                ),
+                message: OwnedParameter::from_serial(&SelfExcludeParams { duration_days: 14 })
+                    .expect("Parameter within size bounds"),
+            },
+        )
+        .expect("Self-exclusion should succeed");
+    
+    let details = check_eligibility_details(&chain, &init, ALICE, 100_000_000);
+    
+    assert_eq!(details.status, EligibilityStatus::OnCooldown);
+    assert_eq!(details.max_eligible_stake, Amount::zero());
+    assert_eq!(
+        details.blocked_until,
+        chain.block_time().checked_add(Duration::from_days(14))
+    );
+}
+
+// ============================================================================
+// TESTS - PER-BET STAKE LIMITS
+// ============================================================================
+
+// Test 23: User-set maximum stake blocks larger single bets
+#[test]
+fn test_user_max_stake() {
+    let verifier = TestVerifier::new();
+    let (mut chain, init) = initialize_with_verifier(&verifier);
+    
+    register_user_with_age_verification(&mut chain, &init, ALICE, ALICE_ADDR, &verifier);
+    
+    let params = SetLimitsParams {
+        max_stake: Some(Amount::from_micro_ccd(200_000_000)),
+        ..limits_params(1_000_000_000, 5_000_000_000)
+    };
+    set_limits_with(&mut chain, &init, ALICE, ALICE_ADDR, params);
+    
+    let details = check_eligibility_details(&chain, &init, ALICE, 300_000_000);
+    assert_eq!(details.status, EligibilityStatus::StakeTooHigh);
+    assert_eq!(details.max_eligible_stake, Amount::from_micro_ccd(200_000_000));
+    
+    assert_eq!(
+        try_record_transaction(&mut chain, &init, ALICE, 300_000_000, "slots"),
+        TransactionOutcome::StakeTooHigh
+    );
+    
+    // A bet at the maximum is still fine
+    record_transaction(&mut chain, &init, ALICE, 200_000_000, "slots");
+}
+
+// Test 24: Contract-wide stake cap applies to everyone and only the admin can set it
+#[test]
+fn test_admin_stake_cap() {
+    let verifier = TestVerifier::new();
+    let (mut chain, init) = initialize_with_verifier(&verifier);
+    
+    register_user_with_age_verification(&mut chain, &init, BOB, BOB_ADDR, &verifier);
+    set_limits(&mut chain, &init, BOB, BOB_ADDR, 1_000_000_000, 5_000_000_000);
+    
+    // BOB is not the admin
+    let error: ContractError = set_stake_cap(&mut chain, &init, BOB, Some(1))
+        .expect_err("Should fail - not admin")
+        .parse_return_value()
+        .expect("Should return ContractError");
+    assert_eq!(error, ContractError::Unauthorized);
+    
+    // ALICE deployed the contract, so she is the admin
+    set_stake_cap(&mut chain, &init, ALICE, Some(100_000_000)).expect("Admin can set cap");
+    
+    assert_eq!(
+        check_eligibility(&chain, &init, BOB, 150_000_000),
+        EligibilityStatus::StakeTooHigh
+    );
+    assert_eq!(
+        try_record_transaction(&mut chain, &init, BOB, 150_000_000, "slots"),
+        TransactionOutcome::StakeTooHigh
+    );
+    
+    // Removing the cap lets the bet through again
+    set_stake_cap(&mut chain, &init, ALICE, None).expect("Admin can remove cap");
+    record_transaction(&mut chain, &init, BOB, 150_000_000, "slots");
+}
+
+// ============================================================================
+// TESTS - BET COUNT LIMITS
+// ============================================================================
+
+// Test 25: Hourly bet count limit blocks further bets until the hour passes
+#[test]
+fn test_hourly_bet_count_limit() {
+    let verifier = TestVerifier::new();
+    let (mut chain, init) = initialize_with_verifier(&verifier);
+    
+    register_user_with_age_verification(&mut chain, &init, ALICE, ALICE_ADDR, &verifier);
+    let params = SetLimitsParams {
+        max_bets_per_hour: Some(2),
+        max_bets_per_day: Some(10),
+        ..limits_params(1_000_000_000, 5_000_000_000)
+    };
+    set_limits_with(&mut chain, &init, ALICE, ALICE_ADDR, params);
+    
+    record_transaction(&mut chain, &init, ALICE, 10_000_000, "slots");
+    record_transaction(&mut chain, &init, ALICE, 10_000_000, "slots");
+    
+    let details = check_eligibility_details(&chain, &init, ALICE, 10_000_000);
+    assert_eq!(details.status, EligibilityStatus::BetCountLimitReached);
+    assert_eq!(
+        details.blocked_until,
+        chain.block_time().checked_add(Duration::from_hours(1))
+    );
+    
+    assert_eq!(
+        try_record_transaction(&mut chain, &init, ALICE, 10_000_000, "slots"),
+        TransactionOutcome::BetCountLimitExceeded
+    );
+    
+    // After an hour the count resets
+    chain
+        .tick_block_time(Duration::from_hours(1))
+        .expect("Block time should not overflow");
+    assert_eq!(
+        check_eligibility(&chain, &init, ALICE, 10_000_000),
+        EligibilityStatus::Eligible
+    );
+    record_transaction(&mut chain, &init, ALICE, 10_000_000, "slots");
+}
+
+// Test 26: Hourly bet limit above the daily one is rejected
+#[test]
+fn test_invalid_bet_count_limits_rejected() {
+    let verifier = TestVerifier::new();
+    let (mut chain, init) = initialize_with_verifier(&verifier);
+    
+    let params = SetLimitsParams {
+        max_bets_per_hour: Some(20),
+        max_bets_per_day: Some(10),
+        ..limits_params(1_000_000_000, 5_000_000_000)
+    };
+    
+    let error: ContractError = chain
+        .contract_update(
+            SIGNER,
+            ALICE,
+            ALICE_ADDR,
+            Energy::from(10_000),
+            UpdateContractPayload {
+                address: init.contract_address,
+                amount: Amount::zero(),
+                receive_name: OwnedReceiveName::new_unchecked(
+                    "safestake_registry.set_limits".to_string()
+                ),
+                message: OwnedParameter::from_serial(&params)
+                    .expect("Parameter within size bounds"),
+            },
+        )
+        .expect_err("Should fail - invalid bet count limits")
+        .parse_return_value()
+        .expect("Should return ContractError");
+    assert_eq!(error, ContractError::InvalidLimits);
+}
+
+// ============================================================================
+// TESTS - SESSION TIME LIMITS
+// ============================================================================
+
+// Test 27: Play time on one platform counts against sessions on every platform
+#[test]
+fn test_session_time_limit_across_platforms() {
+    let verifier = TestVerifier::new();
+    let (mut chain, init) = initialize_with_verifier(&verifier);
+    
+    register_user_with_age_verification(&mut chain, &init, ALICE, ALICE_ADDR, &verifier);
+    let params = SetLimitsParams {
+        daily_time_limit: Some(Duration::from_hours(2)),
+        ..limits_params(1_000_000_000, 5_000_000_000)
+    };
+    set_limits_with(&mut chain, &init, ALICE, ALICE_ADDR, params);
+    
+    // Three hours on the casino
+    update_session(&mut chain, &init, "start_session", ALICE, "casino")
+        .expect("Start session should succeed");
+    chain
+        .tick_block_time(Duration::from_hours(3))
+        .expect("Block time should not overflow");
+    
+    let details = check_eligibility_details(&chain, &init, ALICE, 10_000_000);
+    assert_eq!(details.status, EligibilityStatus::SessionTimeLimitReached);
+    
+    update_session(&mut chain, &init, "end_session", ALICE, "casino")
+        .expect("End session should succeed");
+    
+    // Cannot start again on the sportsbook
+    let error: ContractError =
+        update_session(&mut chain, &init, "start_session", ALICE, "sportsbook")
+            .expect_err("Should fail - daily play time used up")
+            .parse_return_value()
+            .expect("Should return ContractError");
+    assert_eq!(error, ContractError::SessionTimeLimitExceeded);
+    
+    let play_time = get_play_time(&chain, &init, ALICE);
+    assert_eq!(play_time.play_time_today, Duration::from_hours(3));
+    assert_eq!(play_time.daily_time_limit, Some(Duration::from_hours(2)));
+    assert!(play_time.active_sessions.is_empty());
+    
+    // Next day the play time starts over
+    chain
+        .tick_block_time(Duration::from_days(1))
+        .expect("Block time should not overflow");
+    update_session(&mut chain, &init, "start_session", ALICE, "sportsbook")
+        .expect("Start session should succeed on a new day");
+}
+
+// Test 28: Sessions are tracked per platform
+#[test]
+fn test_session_tracking_per_platform() {
+    let verifier = TestVerifier::new();
+    let (mut chain, init) = initialize_with_verifier(&verifier);
+    
+    register_user_with_age_verification(&mut chain, &init, ALICE, ALICE_ADDR, &verifier);
+    
+    update_session(&mut chain, &init, "start_session", ALICE, "casino")
+        .expect("Start session should succeed");
+    let error: ContractError = update_session(&mut chain, &init, "start_session", ALICE, "casino")
+        .expect_err("Should fail - session already open")
+        .parse_return_value()
+        .expect("Should return ContractError");
+    assert_eq!(error, ContractError::SessionAlreadyActive);
+    
+    let error: ContractError = update_session(&mut chain, &init, "end_session", ALICE, "poker")
+        .expect_err("Should fail - no session on this platform")
+        .parse_return_value()
+        .expect("Should return ContractError");
+    assert_eq!(error, ContractError::SessionNotFound);
+    
+    chain
+        .tick_block_time(Duration::from_minutes(45))
+        .expect("Block time should not overflow");
+    
+    let play_time = get_play_time(&chain, &init, ALICE);
+    assert_eq!(play_time.play_time_today, Duration::from_minutes(45));
+    assert_eq!(play_time.active_sessions.len(), 1);
+    assert_eq!(play_time.active_sessions[0].0, "casino");
+}
+
+// ============================================================================
+// TESTS - PER-PLATFORM LIMITS
+// ============================================================================
+
+// Test 29: Platform limit applies on that platform only, global limit everywhere
+#[test]
+fn test_platform_daily_limit() {
+    let verifier = TestVerifier::new();
+    let (mut chain, init) = initialize_with_verifier(&verifier);
+    
+    // 50 CCD/day overall, 20 CCD/day on the casino
+    register_user_with_age_verification(&mut chain, &init, ALICE, ALICE_ADDR, &verifier);
+    set_limits(&mut chain, &init, ALICE, ALICE_ADDR, 50_000_000_000, 500_000_000_000);
+    set_platform_limits(&mut chain, &init, ALICE, "casino", Some(20_000_000_000), None);
+    
+    record_transaction(&mut chain, &init, ALICE, 15_000_000_000, "casino");
+    
+    let casino_check = CheckEligibilityParams {
+        user_account: ALICE,
+        proposed_amount: Amount::from_micro_ccd(10_000_000_000),
+        platform_id: Some("casino".to_string()),
+        category: None,
+    };
+    let details = check_eligibility_with(&chain, &init, &casino_check);
+    assert_eq!(details.status, EligibilityStatus::PlatformDailyLimitReached);
+    assert_eq!(details.max_eligible_stake, Amount::from_micro_ccd(5_000_000_000));
+    
+    assert_eq!(
+        try_record_transaction(&mut chain, &init, ALICE, 10_000_000_000, "casino"),
+        TransactionOutcome::PlatformDailyLimitExceeded
+    );
+    
+    // Other platforms only see the global limit
+    record_transaction(&mut chain, &init, ALICE, 30_000_000_000, "sportsbook");
+    assert_eq!(
+        check_eligibility(&chain, &init, ALICE, 5_000_000_000),
+        EligibilityStatus::Eligible
+    );
+    
+    // The global limit still covers the casino: 45 CCD spent, 5 left overall
+    let details = check_eligibility_with(&chain, &init, &casino_check);
+    assert_eq!(details.status, EligibilityStatus::DailyLimitReached);
+    assert_eq!(details.max_eligible_stake, Amount::from_micro_ccd(5_000_000_000));
+    
+    // Next day both counters start over
+    chain
+        .tick_block_time(Duration::from_days(1))
+        .expect("Block time should not overflow");
+    record_transaction(&mut chain, &init, ALICE, 20_000_000_000, "casino");
+}
+
+// ============================================================================
+// TESTS - PER-PLATFORM EXCLUSION
+// ============================================================================
+
+// Test 30: Excluding one platform blocks it and leaves the others open
+#[test]
+fn test_self_exclude_single_platform() {
+    let verifier = TestVerifier::new();
+    let (mut chain, init) = initialize_with_verifier(&verifier);
+    
+    register_user_with_age_verification(&mut chain, &init, ALICE, ALICE_ADDR, &verifier);
+    set_limits(&mut chain, &init, ALICE, ALICE_ADDR, 1_000_000_000, 5_000_000_000);
+    
+    let params = SelfExcludePlatformsParams {
+        platform_ids: vec!["casino".to_string()],
+        duration_days: 7,
+    };
+    chain
+        .contract_update(
+            SIGNER,
+            ALICE,
+            ALICE_ADDR,
+            Energy::from(10_000),
+            UpdateContractPayload {
+                address: init.contract_address,
+                amount: Amount::zero(),
+                receive_name: OwnedReceiveName::new_unchecked(
+                    "safestake_registry.self_exclude_platforms".to_string()
+                ),
+                message: OwnedParameter::from_serial(&params)
+                    .expect("Parameter within size bounds"),
+            },
+        )
+        .expect("Platform self-exclusion should succeed");
+    
+    let casino_check = CheckEligibilityParams {
+        user_account: ALICE,
+        proposed_amount: Amount::from_micro_ccd(100_000_000),
+        platform_id: Some("casino".to_string()),
+        category: None,
+    };
+    let details = check_eligibility_with(&chain, &init, &casino_check);
+    assert_eq!(details.status, EligibilityStatus::PlatformExcluded);
+    assert_eq!(
+        details.blocked_until,
+        chain.block_time().checked_add(Duration::from_days(7))
+    );
+    
+    assert_eq!(
+        try_record_transaction(&mut chain, &init, ALICE, 100_000_000, "casino"),
+        TransactionOutcome::PlatformExcluded
+    );
+    
+    // Other platforms are unaffected
+    assert_eq!(
+        check_eligibility(&chain, &init, ALICE, 100_000_000),
+        EligibilityStatus::Eligible
+    );
+    record_transaction(&mut chain, &init, ALICE, 100_000_000, "sportsbook");
+    
+    // The exclusion ends on its own
+    chain
+        .tick_block_time(Duration::from_days(7))
+        .expect("Block time should not overflow");
+    record_transaction(&mut chain, &init, ALICE, 100_000_000, "casino");
+}
+
+// ============================================================================
+// TESTS - GAME CATEGORIES
+// ============================================================================
+
+// Test 31: Category limits count bets across platforms and leave other categories open
+#[test]
+fn test_category_limits() {
+    let verifier = TestVerifier::new();
+    let (mut chain, init) = initialize_with_verifier(&verifier);
+    
+    register_user_with_age_verification(&mut chain, &init, ALICE, ALICE_ADDR, &verifier);
+    set_limits(&mut chain, &init, ALICE, ALICE_ADDR, 1_000_000_000, 5_000_000_000);
+    
+    let params = SetCategoryLimitsParams {
+        category: GameCategory::Slots,
+        daily_limit: Some(Amount::from_micro_ccd(200_000_000)),
+        monthly_limit: None,
+    };
+    chain
+        .contract_update(
+            SIGNER,
+            ALICE,
+            ALICE_ADDR,
+            Energy::from(10_000),
+            UpdateContractPayload {
+                address: init.contract_address,
+                amount: Amount::zero(),
+                receive_name: OwnedReceiveName::new_unchecked(
+                    "safestake_registry.set_category_limits".to_string()
+                ),
+                message: OwnedParameter::from_serial(&params)
+                    .expect("Parameter within size bounds"),
+            },
+        )
+        .expect("Set category limits should succeed");
+    
+    assert_eq!(
+        try_record_category_transaction(
+            &mut chain,
+            &init,
+            ALICE,
+            150_000_000,
+            "casino",
+            GameCategory::Slots,
+        ),
+        TransactionOutcome::Recorded
+    );
+    
+    let slots_check = CheckEligibilityParams {
+        user_account: ALICE,
+        proposed_amount: Amount::from_micro_ccd(100_000_000),
+        platform_id: None,
+        category: Some(GameCategory::Slots),
+    };
+    let details = check_eligibility_with(&chain, &init, &slots_check);
+    assert_eq!(details.status, EligibilityStatus::CategoryDailyLimitReached);
+    assert_eq!(details.max_eligible_stake, Amount::from_micro_ccd(50_000_000));
+    
+    // The category limit applies on every platform
+    assert_eq!(
+        try_record_category_transaction(
+            &mut chain,
+            &init,
+            ALICE,
+            100_000_000,
+            "other_casino",
+            GameCategory::Slots,
+        ),
+        TransactionOutcome::CategoryDailyLimitExceeded
+    );
+    
+    // Other categories only count towards the global limits
+    assert_eq!(
+        try_record_category_transaction(
+            &mut chain,
+            &init,
+            ALICE,
+            500_000_000,
+            "casino",
+            GameCategory::Sports,
+        ),
+        TransactionOutcome::Recorded
+    );
+}
+
+// Test 32: Excluding a category blocks it on every platform until the exclusion ends
+#[test]
+fn test_self_exclude_categories() {
+    let verifier = TestVerifier::new();
+    let (mut chain, init) = initialize_with_verifier(&verifier);
+    
+    register_user_with_age_verification(&mut chain, &init, ALICE, ALICE_ADDR, &verifier);
+    set_limits(&mut chain, &init, ALICE, ALICE_ADDR, 1_000_000_000, 5_000_000_000);
+    
+    let params = SelfExcludeCategoriesParams {
+        categories: vec![GameCategory::Slots, GameCategory::Casino],
+        duration_days: 30,
+    };
+    chain
+        .contract_update(
+            SIGNER,
+            ALICE,
+            ALICE_ADDR,
+            Energy::from(10_000),
+            UpdateContractPayload {
+                address: init.contract_address,
+                amount: Amount::zero(),
+                receive_name: OwnedReceiveName::new_unchecked(
+                    "safestake_registry.self_exclude_categories".to_string()
+                ),
+                message: OwnedParameter::from_serial(&params)
+                    .expect("Parameter within size bounds"),
+            },
+        )
+        .expect("Category self-exclusion should succeed");
+    
+    let slots_check = CheckEligibilityParams {
+        user_account: ALICE,
+        proposed_amount: Amount::from_micro_ccd(100_000_000),
+        platform_id: Some("casino".to_string()),
+        category: Some(GameCategory::Slots),
+    };
+    let details = check_eligibility_with(&chain, &init, &slots_check);
+    assert_eq!(details.status, EligibilityStatus::CategoryExcluded);
+    assert_eq!(
+        details.blocked_until,
+        chain.block_time().checked_add(Duration::from_days(30))
+    );
+    
+    assert_eq!(
+        try_record_category_transaction(
+            &mut chain,
+            &init,
+            ALICE,
+            100_000_000,
+            "casino",
+            GameCategory::Slots,
+        ),
+        TransactionOutcome::CategoryExcluded
+    );
+    
+    // Lottery on the same platform is still available
+    assert_eq!(
+        try_record_category_transaction(
+            &mut chain,
+            &init,
+            ALICE,
+            100_000_000,
+            "casino",
+            GameCategory::Lottery,
+        ),
+        TransactionOutcome::Recorded
+    );
+}
+
+// ============================================================================
+// TESTS - OPERATOR AND REGULATOR EXCLUSIONS
+// ============================================================================
+
+// Test 33: Registered operators can exclude users from their own platform only
+#[test]
+fn test_operator_imposed_exclusion() {
+    let verifier = TestVerifier::new();
+    let (mut chain, init) = initialize_with_verifier(&verifier);
+    
+    register_user_with_age_verification(&mut chain, &init, ALICE, ALICE_ADDR, &verifier);
+    set_limits(&mut chain, &init, ALICE, ALICE_ADDR, 1_000_000_000, 5_000_000_000);
+    
+    let exclusion = ImposePlatformExclusionParams {
+        user_account: ALICE,
+        duration_days: 14,
+        reason: ExclusionReason::HarmMarkers,
+    };
+    let error: ContractError =
+        update_as(&mut chain, &init, BOB, "impose_platform_exclusion", &exclusion)
+            .expect_err("Should fail - BOB is not an operator")
+            .parse_return_value()
+            .expect("Should return ContractError");
+    assert_eq!(error, ContractError::Unauthorized);
+    
+    let operator = SetOperatorParams {
+        operator: BOB,
+        platform_id: Some("casino".to_string()),
+    };
+    update_as(&mut chain, &init, ALICE, "set_operator", &operator)
+        .expect("Admin should register the operator");
+    
+    let update = update_as(&mut chain, &init, BOB, "impose_platform_exclusion", &exclusion)
+        .expect("Operator exclusion should succeed");
+    let source = ExclusionSource {
+        issuer: ExclusionIssuer::Operator(BOB),
+        reason: ExclusionReason::HarmMarkers,
+    };
+    let until = chain.block_time().checked_add(Duration::from_days(14));
+    assert_eq!(
+        contract_events(&update),
+        vec![Event::ExclusionImposed(ExclusionEvent {
+            user_account: ALICE,
+            scope: ExclusionScope::Platform("casino".to_string()),
+            until,
+            source,
+        })]
+    );
+    
+    let casino_check = CheckEligibilityParams {
+        user_account: ALICE,
+        proposed_amount: Amount::from_micro_ccd(100_000_000),
+        platform_id: Some("casino".to_string()),
+        category: None,
+    };
+    let details = check_eligibility_with(&chain, &init, &casino_check);
+    assert_eq!(details.status, EligibilityStatus::PlatformExcluded);
+    assert_eq!(details.blocked_until, until);
+    assert_eq!(details.exclusion, Some(source));
+    
+    // The operator's exclusion does not reach other platforms
+    record_transaction(&mut chain, &init, ALICE, 100_000_000, "sportsbook");
+}
+
+// Test 34: Regulators can exclude users from all platforms indefinitely
+#[test]
+fn test_regulator_imposed_exclusion() {
+    let verifier = TestVerifier::new();
+    let (mut chain, init) = initialize_with_verifier(&verifier);
+    
+    register_user_with_age_verification(&mut chain, &init, ALICE, ALICE_ADDR, &verifier);
+    set_limits(&mut chain, &init, ALICE, ALICE_ADDR, 1_000_000_000, 5_000_000_000);
+    
+    let exclusion = ImposeExclusionParams {
+        user_account: ALICE,
+        duration_days: None,
+        reason: ExclusionReason::CourtOrder,
+    };
+    let error: ContractError = update_as(&mut chain, &init, BOB, "impose_exclusion", &exclusion)
+        .expect_err("Should fail - BOB is not a regulator")
+        .parse_return_value()
+        .expect("Should return ContractError");
+    assert_eq!(error, ContractError::Unauthorized);
+    
+    let regulator = RoleParams {
+        account: BOB,
+        role: Role::Regulator,
+    };
+    update_as(&mut chain, &init, ALICE, "grant_role", &regulator)
+        .expect("Admin should grant the regulator role");
+    update_as(&mut chain, &init, BOB, "impose_exclusion", &exclusion)
+        .expect("Regulator exclusion should succeed");
+    
+    // Indefinite, so it is still in force a year later
+    chain
+        .tick_block_time(Duration::from_days(365))
+        .expect("Block time should not overflow");
+    
+    let details = check_eligibility_details(&chain, &init, ALICE, 100_000_000);
+    assert_eq!(details.status, EligibilityStatus::SelfExcluded);
+    assert_eq!(details.blocked_until, None);
+    assert_eq!(
+        details.exclusion,
+        Some(ExclusionSource {
+            issuer: ExclusionIssuer::Regulator(BOB),
+            reason: ExclusionReason::CourtOrder,
+        })
+    );
+    
+    assert_eq!(
+        try_record_transaction(&mut chain, &init, ALICE, 100_000_000, "casino"),
+        TransactionOutcome::SelfExcluded
+    );
+}
+
+// ============================================================================
+// TESTS - TRUSTED CONTACTS
+// ============================================================================
+
+// Test 35: A trusted contact can impose a cooldown up to the consented maximum,
+// which the user cannot cut short
+#[test]
+fn test_trusted_contact_cooldown() {
+    let verifier = TestVerifier::new();
+    let (mut chain, init) = initialize_with_verifier(&verifier);
+    
+    register_user_with_age_verification(&mut chain, &init, ALICE, ALICE_ADDR, &verifier);
+    set_limits(&mut chain, &init, ALICE, ALICE_ADDR, 1_000_000_000, 5_000_000_000);
+    
+    let too_generous = SetTrustedContactParams {
+        contact: BOB,
+        max_cooldown_days: Some(30),
+    };
+    let error: ContractError =
+        update_as(&mut chain, &init, ALICE, "set_trusted_contact", &too_generous)
+            .expect_err("Should fail - over the 7 day maximum")
+            .parse_return_value()
+            .expect("Should return ContractError");
+    assert_eq!(error, ContractError::InvalidLimits);
+    
+    let nomination = SetTrustedContactParams {
+        contact: BOB,
+        max_cooldown_days: Some(3),
+    };
+    update_as(&mut chain, &init, ALICE, "set_trusted_contact", &nomination)
+        .expect("Nominating a trusted contact should succeed");
+    
+    let too_long = TrustedCooldownParams {
+        user_account: ALICE,
+        duration_days: 5,
+    };
+    let error: ContractError = update_as(&mut chain, &init, BOB, "request_cooldown", &too_long)
+        .expect_err("Should fail - longer than ALICE consented to")
+        .parse_return_value()
+        .expect("Should return ContractError");
+    assert_eq!(error, ContractError::CooldownTooLong);
+    
+    let cooldown = TrustedCooldownParams {
+        user_account: ALICE,
+        duration_days: 3,
+    };
+    update_as(&mut chain, &init, BOB, "request_cooldown", &cooldown)
+        .expect("Trusted contact cooldown should succeed");
+    let until = chain.block_time().checked_add(Duration::from_days(3));
+    
+    // Neither a shorter self-exclusion nor removing the contact ends the cooldown
+    update_as(&mut chain, &init, ALICE, "self_exclude", &SelfExcludeParams { duration_days: 0 })
+        .expect("Self-exclusion should succeed");
+    let removal = SetTrustedContactParams {
+        contact: BOB,
+        max_cooldown_days: None,
+    };
+    update_as(&mut chain, &init, ALICE, "set_trusted_contact", &removal)
+        .expect("Removing a trusted contact should succeed");
+    
+    let details = check_eligibility_details(&chain, &init, ALICE, 100_000_000);
+    assert_eq!(details.status, EligibilityStatus::OnCooldown);
+    assert_eq!(details.blocked_until, until);
+    assert_eq!(
+        details.exclusion,
+        Some(ExclusionSource {
+            issuer: ExclusionIssuer::TrustedContact(BOB),
+            reason: ExclusionReason::TrustedContactRequest,
+        })
+    );
+    
+    chain
+        .tick_block_time(Duration::from_days(3))
+        .expect("Block time should not overflow");
+    
+    // ALICE also excluded herself, so she has to ask to come back
+    update_as(&mut chain, &init, ALICE, "request_reactivation", &())
+        .expect("Requesting reactivation should succeed");
+    chain
+        .tick_block_time(Duration::from_hours(24))
+        .expect("Block time should not overflow");
+    record_transaction(&mut chain, &init, ALICE, 100_000_000, "casino");
+}
+
+// ============================================================================
+// TESTS - AUTOMATIC COOLDOWN
+// ============================================================================
+
+// Test 36: Using up the daily limit starts the user's automatic cooldown
+#[test]
+fn test_auto_cooldown_on_limit_reached() {
+    let verifier = TestVerifier::new();
+    let (mut chain, init) = initialize_with_verifier(&verifier);
+    
+    register_user_with_age_verification(&mut chain, &init, ALICE, ALICE_ADDR, &verifier);
+    set_limits(&mut chain, &init, ALICE, ALICE_ADDR, 1_000_000_000, 5_000_000_000);
+    
+    let rule = SetAutoCooldownParams {
+        rule: Some(AutoCooldownRule {
+            cooldown: Duration::from_days(2),
+            on_limit_reached: true,
+            max_rejections: None,
+            rejection_window: Duration::from_millis(0),
+        }),
+    };
+    update_as(&mut chain, &init, ALICE, "set_auto_cooldown", &rule)
+        .expect("Setting the rule should succeed");
+    
+    // Bets below the limit leave the user free to play
+    record_transaction(&mut chain, &init, ALICE, 600_000_000, "casino");
+    assert_eq!(
+        check_eligibility(&chain, &init, ALICE, 100_000_000),
+        EligibilityStatus::Eligible
+    );
+    
+    record_transaction(&mut chain, &init, ALICE, 400_000_000, "casino");
+    
+    // The daily reset alone does not end the cooldown
+    chain
+        .tick_block_time(Duration::from_days(1))
+        .expect("Block time should not overflow");
+    let details = check_eligibility_details(&chain, &init, ALICE, 100_000_000);
+    assert_eq!(details.status, EligibilityStatus::OnCooldown);
+    assert_eq!(
+        details.exclusion,
+        Some(ExclusionSource {
+            issuer: ExclusionIssuer::User,
+            reason: ExclusionReason::AutoCooldown,
+        })
+    );
+    
+    chain
+        .tick_block_time(Duration::from_days(1))
+        .expect("Block time should not overflow");
+    record_transaction(&mut chain, &init, ALICE, 100_000_000, "casino");
+}
+
+// Test 37: Too many rejected bets within the window start a cooldown
+#[test]
+fn test_auto_cooldown_on_rejected_attempts() {
+    let verifier = TestVerifier::new();
+    let (mut chain, init) = initialize_with_verifier(&verifier);
+    
+    register_user_with_age_verification(&mut chain, &init, ALICE, ALICE_ADDR, &verifier);
+    set_limits(&mut chain, &init, ALICE, ALICE_ADDR, 1_000_000_000, 5_000_000_000);
+    
+    let rule = SetAutoCooldownParams {
+        rule: Some(AutoCooldownRule {
+            cooldown: Duration::from_hours(12),
+            on_limit_reached: false,
+            max_rejections: Some(2),
+            rejection_window: Duration::from_hours(1),
+        }),
+    };
+    update_as(&mut chain, &init, ALICE, "set_auto_cooldown", &rule)
+        .expect("Setting the rule should succeed");
+    
+    let entries = (1..=3)
+        .map(|bet_id| BatchTransactionEntry {
+            user_account: ALICE,
+            amount: Amount::from_micro_ccd(if bet_id == 3 { 100_000_000 } else { 2_000_000_000 }),
+            platform_commitment: platform_commitment(ALICE, "casino"),
+            category: GameCategory::Sports,
+            bet_id,
+        })
+        .collect();
+    let outcomes: Vec<TransactionOutcome> =
+        record_transactions_batch(&mut chain, &init, "casino", entries)
+            .into_iter()
+            .map(|result| result.outcome)
+            .collect();
+    
+    // The second rejection starts the cooldown, which then blocks a valid bet
+    assert_eq!(
+        outcomes,
+        vec![
+            TransactionOutcome::DailyLimitExceeded,
+            TransactionOutcome::DailyLimitExceeded,
+            TransactionOutcome::OnCooldown,
+        ]
+    );
+    assert_eq!(
+        check_eligibility_details(&chain, &init, ALICE, 100_000_000).blocked_until,
+        chain.block_time().checked_add(Duration::from_hours(12))
+    );
+}
+
+// ============================================================================
+// TESTS - REJECTED ATTEMPTS
+// ============================================================================
+
+// Test 38: Rejected bets are remembered per hour and day and logged as events
+#[test]
+fn test_rejected_attempts_are_tracked() {
+    let verifier = TestVerifier::new();
+    let (mut chain, init) = initialize_with_verifier(&verifier);
+    
+    register_user_with_age_verification(&mut chain, &init, ALICE, ALICE_ADDR, &verifier);
+    set_limits(&mut chain, &init, ALICE, ALICE_ADDR, 1_000_000_000, 5_000_000_000);
+    
+    let over_limit = RecordTransactionParams {
+        user_account: ALICE,
+        amount: Amount::from_micro_ccd(2_000_000_000),
+        platform_commitment: platform_commitment(ALICE, "casino"),
+        category: GameCategory::Slots,
+    };
+    let operator = platform_operator(&mut chain, &init, "casino");
+    let update = update_as(&mut chain, &init, operator, "record_transaction", &over_limit)
+        .expect("A rejected bet should not fail the call");
+    let outcome: TransactionOutcome = update
+        .parse_return_value()
+        .expect("Should return TransactionOutcome");
+    assert_eq!(outcome, TransactionOutcome::DailyLimitExceeded);
+    assert_eq!(
+        contract_events(&update),
+        vec![Event::BetRejected(BetRejectedEvent {
+            user_account: ALICE,
+            amount: Amount::from_micro_ccd(2_000_000_000),
+            outcome: TransactionOutcome::DailyLimitExceeded,
+        })]
+    );
+    let first_rejection = chain.block_time();
+    
+    chain
+        .tick_block_time(Duration::from_minutes(30))
+        .expect("Block time should not overflow");
+    assert_eq!(
+        try_record_transaction(&mut chain, &init, ALICE, 2_000_000_000, "casino"),
+        TransactionOutcome::DailyLimitExceeded
+    );
+    
+    let invoke = chain
+        .contract_invoke(
+            ALICE,
+            ALICE_ADDR,
+            Energy::from(10_000),
+            UpdateContractPayload {
+                address: init.contract_address,
+                amount: Amount::zero(),
+                receive_name: OwnedReceiveName::new_unchecked(
+                    "safestake_registry.get_rejected_attempts".to_string()
+                ),
+                message: OwnedParameter::from_serial(&ALICE)
+                    .expect("Parameter within size bounds"),
+            },
+        )
+        .expect("Invoke should succeed");
+    let attempts: RejectedAttemptsInfo = invoke
+        .parse_return_value()
+        .expect("Should return RejectedAttemptsInfo");
+    assert_eq!(
+        attempts,
+        RejectedAttemptsInfo {
+            this_hour: 2,
+            today: 2,
+            last_rejected_at: first_rejection.checked_add(Duration::from_minutes(30)),
+        }
+    );
+}
+
+// ============================================================================
+// TESTS - RISK SCORE
+// ============================================================================
+
+// Test 39: Rising stakes across several platforms raise the risk score until a
+// review is required
+#[test]
+fn test_risk_score_requires_review() {
+    let verifier = TestVerifier::new();
+    let (mut chain, init) = initialize_with_verifier(&verifier);
+    
+    register_user_with_age_verification(&mut chain, &init, ALICE, ALICE_ADDR, &verifier);
+    set_limits(&mut chain, &init, ALICE, ALICE_ADDR, 1_000_000_000, 5_000_000_000);
+    
+    let threshold = SetRiskThresholdParams {
+        threshold: Some(50),
+    };
+    let error: ContractError =
+        update_as(&mut chain, &init, BOB, "set_risk_review_threshold", &threshold)
+            .expect_err("Should fail - BOB is not the admin")
+            .parse_return_value()
+            .expect("Should return ContractError");
+    assert_eq!(error, ContractError::Unauthorized);
+    update_as(&mut chain, &init, ALICE, "set_risk_review_threshold", &threshold)
+        .expect("Admin should set the threshold");
+    
+    // Midday, so none of the bets count as late-night activity
+    chain
+        .tick_block_time(Duration::from_hours(12))
+        .expect("Block time should not overflow");
+    
+    record_transaction(&mut chain, &init, ALICE, 10_000_000, "casino_a");
+    record_transaction(&mut chain, &init, ALICE, 20_000_000, "casino_b");
+    record_transaction(&mut chain, &init, ALICE, 30_000_000, "casino_a");
+    assert_eq!(
+        check_eligibility(&chain, &init, ALICE, 10_000_000),
+        EligibilityStatus::Eligible
+    );
+    
+    record_transaction(&mut chain, &init, ALICE, 40_000_000, "casino_c");
+    
+    let invoke = chain
+        .contract_invoke(
+            ALICE,
+            ALICE_ADDR,
+            Energy::from(10_000),
+            UpdateContractPayload {
+                address: init.contract_address,
+                amount: Amount::zero(),
+                receive_name: OwnedReceiveName::new_unchecked(
+                    "safestake_registry.get_risk_profile".to_string()
+                ),
+                message: OwnedParameter::from_serial(&ALICE)
+                    .expect("Parameter within size bounds"),
+            },
+        )
+        .expect("Invoke should succeed");
+    let risk: RiskProfile = invoke
+        .parse_return_value()
+        .expect("Should return RiskProfile");
+    assert_eq!(risk.stake_increases, 3);
+    assert_eq!(risk.platforms_today, 3);
+    assert_eq!(risk.late_night_bets, 0);
+    assert_eq!(risk.score, 50);
+    
+    // The review is advisory, so the allowance is still reported
+    let details = check_eligibility_details(&chain, &init, ALICE, 10_000_000);
+    assert_eq!(details.status, EligibilityStatus::ReviewRequired);
+    assert_eq!(details.remaining_daily, Amount::from_micro_ccd(900_000_000));
+    assert_eq!(details.max_eligible_stake, Amount::from_micro_ccd(900_000_000));
+}
+
+// ============================================================================
+// TESTS - AFFORDABILITY CHECKS
+// ============================================================================
+
+// Test 40: Passing the affordability threshold needs a signed attestation
+#[test]
+fn test_affordability_threshold_requires_attestation() {
+    let verifier = TestVerifier::new();
+    let (mut chain, init) = initialize_with_verifier(&verifier);
+    
+    register_user_with_age_verification(&mut chain, &init, ALICE, ALICE_ADDR, &verifier);
+    set_limits(&mut chain, &init, ALICE, ALICE_ADDR, 1_000_000_000, 5_000_000_000);
+    
+    let threshold = SetAffordabilityThresholdParams {
+        threshold: Some(Amount::from_micro_ccd(1_500_000_000)),
+    };
+    update_as(&mut chain, &init, ALICE, "set_affordability_threshold", &threshold)
+        .expect("Admin should set the threshold");
+    
+    record_transaction(&mut chain, &init, ALICE, 1_000_000_000, "casino");
+    chain
+        .tick_block_time(Duration::from_days(1))
+        .expect("Block time should not overflow");
+    
+    let details = check_eligibility_details(&chain, &init, ALICE, 600_000_000);
+    assert_eq!(details.status, EligibilityStatus::AffordabilityCheckRequired);
+    assert_eq!(details.max_eligible_stake, Amount::from_micro_ccd(500_000_000));
+    assert_eq!(
+        try_record_transaction(&mut chain, &init, ALICE, 600_000_000, "casino"),
+        TransactionOutcome::AffordabilityCheckRequired
+    );
+    
+    // Only the verifier's signature is accepted
+    let valid_until = chain
+        .block_time()
+        .checked_add(Duration::from_days(90))
+        .expect("Timestamp should not overflow");
+    let message = attestation_message(init.contract_address, ALICE, 0, valid_until);
+    let forged = AffordabilityAttestationParams {
+        account: ALICE,
+        nonce: 0,
+        valid_until,
+        signature: SignatureEd25519(
+            TestVerifier::new_with_seed(2).signing_key.sign(&message).to_bytes(),
+        ),
+    };
+    let error: ContractError =
+        update_as(&mut chain, &init, BOB, "submit_affordability_attestation", &forged)
+            .expect_err("Should fail - not signed by the verifier")
+            .parse_return_value()
+            .expect("Should return ContractError");
+    assert_eq!(error, ContractError::InvalidSignature);
+    
+    let attestation = AffordabilityAttestationParams {
+        account: ALICE,
+        nonce: 0,
+        valid_until,
+        signature: SignatureEd25519(verifier.signing_key.sign(&message).to_bytes()),
+    };
+    update_as(&mut chain, &init, BOB, "submit_affordability_attestation", &attestation)
+        .expect("Verifier attestation should be accepted");
+    
+    record_transaction(&mut chain, &init, ALICE, 600_000_000, "casino");
+}
+
+// ============================================================================
+// TESTS - UPGRADES
+// ============================================================================
+
+// Test 41: Upgrading and migrating keeps registered users and their exclusions
+#[test]
+fn test_upgrade_keeps_users_and_exclusions() {
+    let verifier = TestVerifier::new();
+    let (mut chain, init) = initialize_with_verifier(&verifier);
+    
+    register_user_with_age_verification(&mut chain, &init, ALICE, ALICE_ADDR, &verifier);
+    register_user_with_age_verification(&mut chain, &init, BOB, BOB_ADDR, &verifier);
+    set_limits(&mut chain, &init, BOB, BOB_ADDR, 1_000_000, 10_000_000);
+    update_as(&mut chain, &init, ALICE, "self_exclude", &SelfExcludeParams { duration_days: 7 })
+        .expect("Self-exclusion should succeed");
+    
+    let module = chain
+        .get_contract(init.contract_address)
+        .expect("Contract should exist")
+        .module_reference;
+    let params = UpgradeParams {
+        module,
+        migrate: Some((
+            OwnedEntrypointName::new_unchecked("migrate".to_string()),
+            OwnedParameter::empty(),
+        )),
+    };
+    
+    // Only the admin may upgrade, and migrate can only be reached through upgrade
+    let error: ContractError = update_as(&mut chain, &init, BOB, "upgrade", &params)
+        .expect_err("Should fail - BOB is not the admin")
+        .parse_return_value()
+        .expect("Should return ContractError");
+    assert_eq!(error, ContractError::Unauthorized);
+    let error: ContractError = update_as(&mut chain, &init, ALICE, "migrate", &())
+        .expect_err("Should fail - migrate is only called by the contract")
+        .parse_return_value()
+        .expect("Should return ContractError");
+    assert_eq!(error, ContractError::Unauthorized);
+    
+    update_as(&mut chain, &init, ALICE, "upgrade", &params).expect("Admin should upgrade");
+    
+    let version: u32 = chain
+        .contract_invoke(
+            ALICE,
+            ALICE_ADDR,
+            Energy::from(10_000),
+            UpdateContractPayload {
+                address: init.contract_address,
+                amount: Amount::zero(),
+                receive_name: OwnedReceiveName::new_unchecked(
+                    "safestake_registry.get_state_version".to_string()
+                ),
+                message: OwnedParameter::empty(),
+            },
+        )
+        .expect("Invoke should succeed")
+        .parse_return_value()
+        .expect("Should return u32");
+    assert_eq!(version, 11);
+    
+    assert!(!migrate_users(&mut chain, &init, 100));
+    
+    assert_eq!(check_eligibility(&chain, &init, ALICE, 100), EligibilityStatus::OnCooldown);
+    assert_eq!(check_eligibility(&chain, &init, BOB, 100), EligibilityStatus::Eligible);
+    assert_eq!(
+        check_eligibility(&chain, &init, BOB, 2_000_000),
+        EligibilityStatus::DailyLimitReached
+    );
+    record_transaction(&mut chain, &init, BOB, 500_000, "casino");
+}
+
+// ============================================================================
+// TESTS - EMERGENCY PAUSE
+// ============================================================================
+
+// Test 42: Pausing halts registration, bets and raising limits, but users can
+// still self-exclude and lower their limits
+#[test]
+fn test_pause_keeps_safety_actions_available() {
+    let verifier = TestVerifier::new();
+    let (mut chain, init) = initialize_with_verifier(&verifier);
+    
+    register_user_with_age_verification(&mut chain, &init, ALICE, ALICE_ADDR, &verifier);
+    set_limits(&mut chain, &init, ALICE, ALICE_ADDR, 1_000_000, 10_000_000);
+    
+    let pause = PauseFlags {
+        registration: true,
+        transactions: true,
+        limits: true,
+    };
+    let error: ContractError = update_as(&mut chain, &init, BOB, "set_paused", &pause)
+        .expect_err("Should fail - BOB is not the admin")
+        .parse_return_value()
+        .expect("Should return ContractError");
+    assert_eq!(error, ContractError::Unauthorized);
+    update_as(&mut chain, &init, ALICE, "set_paused", &pause).expect("Admin should pause");
+    
+    let paused_calls: [(&str, Result<ContractInvokeSuccess, ContractInvokeError>); 3] = [
+        (
+            "register_user",
+            update_as(&mut chain, &init, BOB, "register_user", &RegisterUserParams {
+                account: BOB,
+                signature: verifier.sign_account(BOB),
+            }),
+        ),
+        (
+            "record_transaction",
+            update_as(&mut chain, &init, CHARLIE, "record_transaction", &RecordTransactionParams {
+                user_account: ALICE,
+                amount: Amount::from_micro_ccd(100),
+                platform_commitment: platform_commitment(ALICE, "casino"),
+                category: GameCategory::Casino,
+            }),
+        ),
+        (
+            "set_limits",
+            update_as(&mut chain, &init, ALICE, "set_limits", &limits_params(2_000_000, 10_000_000)),
+        ),
+    ];
+    for (entrypoint, result) in paused_calls {
+        let error: ContractError = result
+            .expect_err(entrypoint)
+            .parse_return_value()
+            .expect("Should return ContractError");
+        assert_eq!(error, ContractError::Paused, "{}", entrypoint);
+    }
+    
+    // Lowering limits and self-excluding still work
+    set_limits(&mut chain, &init, ALICE, ALICE_ADDR, 500_000, 5_000_000);
+    update_as(&mut chain, &init, ALICE, "self_exclude", &SelfExcludeParams { duration_days: 1 })
+        .expect("Self-exclusion is never paused");
+    
+    update_as(&mut chain, &init, ALICE, "set_paused", &PauseFlags::default())
+        .expect("Admin should resume");
+    register_user_with_age_verification(&mut chain, &init, BOB, BOB_ADDR, &verifier);
+}
+
+// ============================================================================
+// TESTS - ROLES
+// ============================================================================
+
+// Test 43: The admin grants and revokes roles, and each role unlocks only its
+// own actions
+#[test]
+fn test_roles_grant_and_revoke() {
+    let verifier = TestVerifier::new();
+    let (mut chain, init) = initialize_with_verifier(&verifier);
+    
+    register_user_with_age_verification(&mut chain, &init, BOB, BOB_ADDR, &verifier);
+    set_limits(&mut chain, &init, BOB, BOB_ADDR, 1_000_000, 10_000_000);
+    
+    let support = RoleParams {
+        account: CHARLIE,
+        role: Role::Support,
+    };
+    let error: ContractError = update_as(&mut chain, &init, BOB, "grant_role", &support)
+        .expect_err("Should fail - BOB is not the admin")
+        .parse_return_value()
+        .expect("Should return ContractError");
+    assert_eq!(error, ContractError::Unauthorized);
+    
+    let update = update_as(&mut chain, &init, ALICE, "grant_role", &support)
+        .expect("Admin should grant the support role");
+    assert_eq!(
+        contract_events(&update),
+        vec![Event::RoleGranted(RoleParams {
+            account: CHARLIE,
+            role: Role::Support,
+        })]
+    );
+    
+    // Support staff can lower a user's limits but never raise them
+    let lower = SupportLowerLimitsParams {
+        user_account: BOB,
+        limits: limits_params(500_000, 5_000_000),
+    };
+    update_as(&mut chain, &init, CHARLIE, "support_lower_limits", &lower)
+        .expect("Support should lower limits");
+    assert_eq!(
+        check_eligibility(&chain, &init, BOB, 600_000),
+        EligibilityStatus::DailyLimitReached
+    );
+    let raise = SupportLowerLimitsParams {
+        user_account: BOB,
+        limits: limits_params(2_000_000, 10_000_000),
+    };
+    let error: ContractError = update_as(&mut chain, &init, CHARLIE, "support_lower_limits", &raise)
+        .expect_err("Should fail - support cannot raise limits")
+        .parse_return_value()
+        .expect("Should return ContractError");
+    assert_eq!(error, ContractError::Unauthorized);
+    
+    // Support staff are not auditors
+    let risk_view = UpdateContractPayload {
+        address: init.contract_address,
+        amount: Amount::zero(),
+        receive_name: OwnedReceiveName::new_unchecked(
+            "safestake_registry.get_risk_profile".to_string()
+        ),
+        message: OwnedParameter::from_serial(&BOB).expect("Parameter within size bounds"),
+    };
+    let error: ContractError = chain
+        .contract_invoke(CHARLIE, Address::Account(CHARLIE), Energy::from(10_000), risk_view.clone())
+        .expect_err("Should fail - CHARLIE is not an auditor")
+        .parse_return_value()
+        .expect("Should return ContractError");
+    assert_eq!(error, ContractError::Unauthorized);
+    
+    let update = update_as(&mut chain, &init, ALICE, "revoke_role", &support)
+        .expect("Admin should revoke the support role");
+    assert_eq!(
+        contract_events(&update),
+        vec![Event::RoleRevoked(RoleParams {
+            account: CHARLIE,
+            role: Role::Support,
+        })]
+    );
+    update_as(&mut chain, &init, CHARLIE, "support_lower_limits", &lower)
+        .expect_err("Should fail - the support role was revoked");
+    
+    let auditor = RoleParams {
+        account: CHARLIE,
+        role: Role::Auditor,
+    };
+    update_as(&mut chain, &init, ALICE, "grant_role", &auditor)
+        .expect("Admin should grant the auditor role");
+    chain
+        .contract_invoke(CHARLIE, Address::Account(CHARLIE), Energy::from(10_000), risk_view)
+        .expect("Auditor should read the risk profile");
+}
+
+// ============================================================================
+// TESTS - WALLET CONTRACTS
+// ============================================================================
+
+// Test 44: A wallet contract authorized by a user can set their limits and
+// self-exclude them, while other contracts are refused
+#[test]
+fn test_authorized_wallet_acts_for_user() {
+    let verifier = TestVerifier::new();
+    let (mut chain, init) = initialize_with_verifier(&verifier);
+    
+    register_user_with_age_verification(&mut chain, &init, BOB, BOB_ADDR, &verifier);
+    register_user_with_age_verification(&mut chain, &init, CHARLIE, Address::Account(CHARLIE), &verifier);
+    
+    // A second instance stands in for BOB's wallet contract
+    let module = chain
+        .get_contract(init.contract_address)
+        .expect("Contract should exist")
+        .module_reference;
+    let wallet = chain
+        .contract_init(
+            SIGNER,
+            BOB,
+            Energy::from(10_000),
+            InitContractPayload {
+                amount: Amount::zero(),
+                mod_ref: module,
+                init_name: OwnedContractName::new_unchecked(
+                    "init_safestake_registry".to_string()
+                ),
+                param: OwnedParameter::from_serial(&InitParams {
+                    verifier_key: verifier.public_key,
+                })
+                .expect("Serialize init params"),
+            },
+        )
+        .expect("Initializing wallet contract")
+        .contract_address;
+    
+    let error: ContractError = update_from_contract(
+        &mut chain,
+        &init,
+        BOB,
+        wallet,
+        "set_limits",
+        &limits_params(1_000_000, 10_000_000),
+    )
+    .expect_err("Should fail - wallet is not authorized yet")
+    .parse_return_value()
+    .expect("Should return ContractError");
+    assert_eq!(error, ContractError::ContractSenderNotAllowed);
+    
+    update_as(&mut chain, &init, BOB, "set_wallet", &SetWalletParams { wallet: Some(wallet) })
+        .expect("BOB should authorize the wallet");
+    let error: ContractError =
+        update_as(&mut chain, &init, CHARLIE, "set_wallet", &SetWalletParams { wallet: Some(wallet) })
+            .expect_err("Should fail - wallet belongs to BOB")
+            .parse_return_value()
+            .expect("Should return ContractError");
+    assert_eq!(error, ContractError::WalletInUse);
+    
+    update_from_contract(
+        &mut chain,
+        &init,
+        BOB,
+        wallet,
+        "set_limits",
+        &limits_params(1_000_000, 10_000_000),
+    )
+    .expect("Wallet should set BOB's limits");
+    assert_eq!(check_eligibility(&chain, &init, BOB, 500_000), EligibilityStatus::Eligible);
+    
+    update_from_contract(&mut chain, &init, BOB, wallet, "self_exclude", &SelfExcludeParams {
+        duration_days: 7,
+    })
+    .expect("Wallet should self-exclude BOB");
+    assert_eq!(check_eligibility(&chain, &init, BOB, 500_000), EligibilityStatus::OnCooldown);
+    
+    // Once removed, the wallet can no longer act for BOB
+    update_as(&mut chain, &init, BOB, "set_wallet", &SetWalletParams { wallet: None })
+        .expect("BOB should remove the wallet");
+    let error: ContractError = update_from_contract(
+        &mut chain,
+        &init,
+        BOB,
+        wallet,
+        "set_limits",
+        &limits_params(500_000, 5_000_000),
+    )
+    .expect_err("Should fail - wallet was removed")
+    .parse_return_value()
+    .expect("Should return ContractError");
+    assert_eq!(error, ContractError::ContractSenderNotAllowed);
+}
+
+// ============================================================================
+// TESTS - SIGNED PERMITS
+// ============================================================================
+
+// Test 45: A user without CCD signs set_limits and self_exclude messages that
+// someone else submits, and each message can only be used once
+#[test]
+fn test_permit_actions_signed_by_user() {
+    let verifier = TestVerifier::new();
+    let (mut chain, init) = initialize_with_verifier(&verifier);
+    
+    // DAVE holds no CCD, so BOB submits and pays for everything
+    let dave_key = TestVerifier::new_with_seed(4).signing_key;
+    chain.create_account(Account::new_with_keys(
+        DAVE,
+        AccountBalance::new(Amount::zero(), Amount::zero(), Amount::zero())
+            .expect("Valid balance"),
+        AccountAccessStructure::singleton(dave_key.verifying_key()),
+    ));
+    update_as(&mut chain, &init, BOB, "register_user", &RegisterUserParams {
+        account: DAVE,
+        signature: verifier.sign_account(DAVE),
+    })
+    .expect("Register user should succeed");
+    
+    let expiry = chain
+        .block_time()
+        .checked_add(Duration::from_hours(1))
+        .expect("Timestamp should not overflow");
+    let set_limits = PermitMessage {
+        contract_address: init.contract_address,
+        nonce: 0,
+        expiry,
+        action: PermitAction::SetLimits(limits_params(1_000_000, 10_000_000)),
+    };
+    let permit = PermitParams {
+        signer: DAVE,
+        signature: sign_permit(&dave_key, DAVE, &set_limits),
+        message: set_limits,
+    };
+    update_as(&mut chain, &init, BOB, "permit", &permit).expect("Signed permit should succeed");
+    assert_eq!(check_eligibility(&chain, &init, DAVE, 500_000), EligibilityStatus::Eligible);
+    
+    let permit_error = |chain: &mut Chain, permit: &PermitParams| -> ContractError {
+        update_as(chain, &init, BOB, "permit", permit)
+            .expect_err("Permit should be refused")
+            .parse_return_value()
+            .expect("Should return ContractError")
+    };
+    assert_eq!(permit_error(&mut chain, &permit), ContractError::NonceMismatch);
+    
+    let self_exclude = |expiry| PermitMessage {
+        contract_address: init.contract_address,
+        nonce: 1,
+        expiry,
+        action: PermitAction::SelfExclude(SelfExcludeParams { duration_days: 7 }),
+    };
+    let forged = PermitParams {
+        signer: DAVE,
+        signature: sign_permit(&TestVerifier::new_with_seed(5).signing_key, DAVE, &self_exclude(expiry)),
+        message: self_exclude(expiry),
+    };
+    assert_eq!(permit_error(&mut chain, &forged), ContractError::InvalidSignature);
+    
+    let now = chain.block_time();
+    let expired = PermitParams {
+        signer: DAVE,
+        signature: sign_permit(&dave_key, DAVE, &self_exclude(now)),
+        message: self_exclude(now),
+    };
+    assert_eq!(permit_error(&mut chain, &expired), ContractError::PermitExpired);
+    
+    let permit = PermitParams {
+        signer: DAVE,
+        signature: sign_permit(&dave_key, DAVE, &self_exclude(expiry)),
+        message: self_exclude(expiry),
+    };
+    update_as(&mut chain, &init, BOB, "permit", &permit).expect("Signed permit should succeed");
+    assert_eq!(check_eligibility(&chain, &init, DAVE, 500_000), EligibilityStatus::OnCooldown);
+    
+    let nonce: u64 = chain
+        .contract_invoke(
+            BOB,
+            BOB_ADDR,
+            Energy::from(10_000),
+            UpdateContractPayload {
+                address: init.contract_address,
+                amount: Amount::zero(),
+                receive_name: OwnedReceiveName::new_unchecked(
+                    "safestake_registry.get_permit_nonce".to_string()
+                ),
+                message: OwnedParameter::from_serial(&DAVE)
+                    .expect("Parameter within size bounds"),
+            },
+        )
+        .expect("Invoke should succeed")
+        .parse_return_value()
+        .expect("Should return u64");
+    assert_eq!(nonce, 2);
+}
+
+// ============================================================================
+// TESTS - AMOUNT OVERFLOW
+// ============================================================================
+
+// Record a bet, returning the contract's error instead of panicking
+fn record_or_error(
+    chain: &mut Chain,
+    init: &ContractInitSuccess,
+    amount: u64,
+) -> Result<TransactionOutcome, ContractError> {
+    let params = RecordTransactionParams {
+        user_account: ALICE,
+        amount: Amount::from_micro_ccd(amount),
+        platform_commitment: platform_commitment(ALICE, "casino"),
+        category: GameCategory::Casino,
+    };
+    let operator = platform_operator(chain, init, "casino");
+    match update_as(chain, init, operator, "record_transaction", &params) {
+        Ok(update) => Ok(update.parse_return_value().expect("Should return TransactionOutcome")),
+        Err(error) => Err(error.parse_return_value().expect("Should return ContractError")),
+    }
+}
+
+// Check eligibility, returning the contract's error instead of panicking
+fn eligibility_or_error(
+    chain: &Chain,
+    init: &ContractInitSuccess,
+    amount: u64,
+) -> Result<EligibilityStatus, ContractError> {
+    let params = CheckEligibilityParams {
+        user_account: ALICE,
+        proposed_amount: Amount::from_micro_ccd(amount),
+        platform_id: Some("casino".to_string()),
+        category: Some(GameCategory::Casino),
+    };
+    let invoke = chain.contract_invoke(
+        BOB,
+        BOB_ADDR,
+        Energy::from(10_000),
+        UpdateContractPayload {
+            address: init.contract_address,
+            amount: Amount::zero(),
+            receive_name: OwnedReceiveName::new_unchecked(
+                "safestake_registry.check_eligibility".to_string()
+            ),
+            message: OwnedParameter::from_serial(&params).expect("Parameter within size bounds"),
+        },
+    );
+    match invoke {
+        Ok(invoke) => {
+            let response: EligibilityResponse =
+                invoke.parse_return_value().expect("Should return EligibilityResponse");
+            Ok(response.status)
+        }
+        Err(error) => Err(error.parse_return_value().expect("Should return ContractError")),
+    }
+}
+
+// A user whose limits are the largest representable amount, so only
+// overflow can stop a bet
+fn initialize_with_maximum_limits() -> (Chain, ContractInitSuccess) {
+    let verifier = TestVerifier::new();
+    let (mut chain, init) = initialize_with_verifier(&verifier);
+    
+    register_user_with_age_verification(&mut chain, &init, ALICE, ALICE_ADDR, &verifier);
+    set_limits(&mut chain, &init, ALICE, ALICE_ADDR, u64::MAX, u64::MAX);
+    
+    let limits = SetPlatformLimitsParams {
+        platform_id: "casino".to_string(),
+        daily_limit: Some(Amount::from_micro_ccd(u64::MAX)),
+        monthly_limit: Some(Amount::from_micro_ccd(u64::MAX)),
+    };
+    update_as(&mut chain, &init, ALICE, "set_platform_limits", &limits)
+        .expect("Set platform limits should succeed");
+    
+    (chain, init)
+}
+
+proptest! {
+    #![proptest_config(ProptestConfig::with_cases(32))]
+    
+    // Test 46: A second bet is recorded exactly when the total still fits in an
+    // amount, and is otherwise refused as over the limit rather than wrapping
+    #[test]
+    fn test_record_transaction_never_wraps(first in 1..=u64::MAX, second in any::<u64>()) {
+        let (mut chain, init) = initialize_with_maximum_limits();
+        
+        prop_assert_eq!(record_or_error(&mut chain, &init, first), Ok(TransactionOutcome::Recorded));
+        
+        let expected = match first.checked_add(second) {
+            Some(_) => Ok(TransactionOutcome::Recorded),
+            None => Ok(TransactionOutcome::DailyLimitExceeded),
+        };
+        prop_assert_eq!(record_or_error(&mut chain, &init, second), expected);
+        
+        // A refused bet leaves the user's spending untouched
+        let details = check_eligibility_details(&chain, &init, ALICE, 0);
+        let spent = u64::MAX - details.remaining_daily.micro_ccd;
+        prop_assert_eq!(spent, first.checked_add(second).unwrap_or(first));
+    }
+    
+    // Test 47: check_eligibility agrees with record_transaction on extreme amounts
+    #[test]
+    fn test_check_eligibility_never_wraps(first in 1..=u64::MAX, second in any::<u64>()) {
+        let (mut chain, init) = initialize_with_maximum_limits();
+        
+        prop_assert_eq!(eligibility_or_error(&chain, &init, first), Ok(EligibilityStatus::Eligible));
+        prop_assert_eq!(record_or_error(&mut chain, &init, first), Ok(TransactionOutcome::Recorded));
+        
+        let expected = match first.checked_add(second) {
+            Some(_) => Ok(EligibilityStatus::Eligible),
+            None => Ok(EligibilityStatus::DailyLimitReached),
+        };
+        prop_assert_eq!(eligibility_or_error(&chain, &init, second), expected);
+    }
+}
+
+// ============================================================================
+// TESTS - SHARED ELIGIBILITY ENGINE
+// ============================================================================
+
+// Result record_transaction should give for a bet check_eligibility answered
+// with. A review is advisory, so the bet is still recorded.
+fn outcome_for_status(status: &EligibilityStatus) -> Result<TransactionOutcome, ContractError> {
+    let outcome = match status {
+        EligibilityStatus::Eligible | EligibilityStatus::ReviewRequired => {
+            TransactionOutcome::Recorded
+        }
+        EligibilityStatus::DailyLimitReached => TransactionOutcome::DailyLimitExceeded,
+        EligibilityStatus::MonthlyLimitReached => TransactionOutcome::MonthlyLimitExceeded,
+        EligibilityStatus::SelfExcluded => TransactionOutcome::SelfExcluded,
+        EligibilityStatus::OnCooldown => TransactionOutcome::OnCooldown,
+        EligibilityStatus::NotRegistered => TransactionOutcome::NotRegistered,
+        EligibilityStatus::AgeNotVerified => TransactionOutcome::AgeNotVerified,
+        EligibilityStatus::StakeTooHigh => TransactionOutcome::StakeTooHigh,
+        EligibilityStatus::BetCountLimitReached => TransactionOutcome::BetCountLimitExceeded,
+        EligibilityStatus::SessionTimeLimitReached => TransactionOutcome::SessionTimeLimitExceeded,
+        EligibilityStatus::PlatformDailyLimitReached => TransactionOutcome::PlatformDailyLimitExceeded,
+        EligibilityStatus::PlatformMonthlyLimitReached => {
+            TransactionOutcome::PlatformMonthlyLimitExceeded
+        }
+        EligibilityStatus::PlatformExcluded => TransactionOutcome::PlatformExcluded,
+        EligibilityStatus::CategoryDailyLimitReached => TransactionOutcome::CategoryDailyLimitExceeded,
+        EligibilityStatus::CategoryMonthlyLimitReached => {
+            TransactionOutcome::CategoryMonthlyLimitExceeded
+        }
+        EligibilityStatus::CategoryExcluded => TransactionOutcome::CategoryExcluded,
+        EligibilityStatus::AffordabilityCheckRequired => TransactionOutcome::AffordabilityCheckRequired,
+        EligibilityStatus::PendingReactivation => TransactionOutcome::PendingReactivation,
+        EligibilityStatus::TransactionsPaused => return Err(ContractError::Paused),
+    };
+    Ok(outcome)
+}
+
+// Ask check_eligibility about a bet, then place it, and return both answers
+fn check_then_record(
+    chain: &mut Chain,
+    init: &ContractInitSuccess,
+    amount: u64,
+    platform_id: &str,
+    category: GameCategory,
+) -> (EligibilityStatus, Result<TransactionOutcome, ContractError>) {
+    let params = CheckEligibilityParams {
+        user_account: BOB,
+        proposed_amount: Amount::from_micro_ccd(amount),
+        platform_id: Some(platform_id.to_string()),
+        category: Some(category),
+    };
+    let status = check_eligibility_with(chain, init, &params).status;
+    
+    let bet = RecordTransactionParams {
+        user_account: BOB,
+        amount: Amount::from_micro_ccd(amount),
+        platform_commitment: platform_commitment(BOB, platform_id),
+        category,
+    };
+    let operator = platform_operator(chain, init, platform_id);
+    let outcome = match update_as(chain, init, operator, "record_transaction", &bet) {
+        Ok(update) => Ok(update.parse_return_value().expect("Should return TransactionOutcome")),
+        Err(error) => Err(error.parse_return_value().expect("Should return ContractError")),
+    };
+    (status, outcome)
+}
+
+// Test 48: A user who is both over their limit and on a cooldown gets the same
+// answer from check_eligibility and record_transaction
+#[test]
+fn test_exclusion_reported_before_limits_by_both_entrypoints() {
+    let verifier = TestVerifier::new();
+    let (mut chain, init) = initialize_with_verifier(&verifier);
+    
+    register_user_with_age_verification(&mut chain, &init, BOB, BOB_ADDR, &verifier);
+    set_limits(&mut chain, &init, BOB, BOB_ADDR, 1_000_000, 10_000_000);
+    update_as(&mut chain, &init, BOB, "self_exclude", &SelfExcludeParams { duration_days: 1 })
+        .expect("Self-exclusion should succeed");
+    
+    let (status, outcome) =
+        check_then_record(&mut chain, &init, 2_000_000, "casino", GameCategory::Casino);
+    assert_eq!(status, EligibilityStatus::OnCooldown);
+    assert_eq!(outcome, Ok(TransactionOutcome::OnCooldown));
+}
+
+// A step in a randomly generated betting session
+#[derive(Debug, Clone)]
+enum SessionStep {
+    Bet { amount: u64, platform: usize, category: usize },
+    Wait { minutes: u64 },
+    SelfExclude { days: u32 },
+    ExcludePlatform { platform: usize, days: u32 },
+    StartSession { platform: usize },
+    EndSession { platform: usize },
+    Pause { transactions: bool },
+}
+
+const SESSION_PLATFORMS: [&str; 2] = ["casino", "sportsbook"];
+const SESSION_CATEGORIES: [GameCategory; 2] = [GameCategory::Slots, GameCategory::Sports];
+
+fn session_step() -> impl Strategy<Value = SessionStep> {
+    prop_oneof![
+        6 => (0..1_500u64, 0..2usize, 0..2usize)
+            .prop_map(|(amount, platform, category)| SessionStep::Bet { amount, platform, category }),
+        2 => (0..2_000u64).prop_map(|minutes| SessionStep::Wait { minutes }),
+        1 => (0..2u32).prop_map(|days| SessionStep::SelfExclude { days }),
+        1 => (0..2usize, 0..2u32).prop_map(|(platform, days)| SessionStep::ExcludePlatform { platform, days }),
+        1 => (0..2usize).prop_map(|platform| SessionStep::StartSession { platform }),
+        1 => (0..2usize).prop_map(|platform| SessionStep::EndSession { platform }),
+        1 => any::<bool>().prop_map(|transactions| SessionStep::Pause { transactions }),
+    ]
+}
+
+proptest! {
+    #![proptest_config(ProptestConfig::with_cases(24))]
+    
+    // Test 49: Throughout random sessions of bets, waits, exclusions, play
+    // sessions and pauses, under random limits and thresholds,
+    // check_eligibility says Eligible (or the advisory ReviewRequired) exactly
+    // when record_transaction records the bet, and otherwise names the reason
+    // record_transaction refuses it for
+    #[test]
+    fn test_check_eligibility_matches_record_transaction(
+        daily in 100..2_000u64,
+        monthly_extra in 0..3_000u64,
+        max_stake in proptest::option::of(50..1_000u64),
+        max_bets_per_hour in proptest::option::of(1..6u32),
+        time_limit_minutes in proptest::option::of(1..600u64),
+        casino_daily in proptest::option::of(50..1_500u64),
+        slots_daily in proptest::option::of(50..1_500u64),
+        review_threshold in proptest::option::of(0..=60u8),
+        affordability_threshold in proptest::option::of(0..2_000u64),
+        steps in proptest::collection::vec(session_step(), 1..12),
+    ) {
+        let verifier = TestVerifier::new();
+        let (mut chain, init) = initialize_with_verifier(&verifier);
+        
+        register_user_with_age_verification(&mut chain, &init, BOB, BOB_ADDR, &verifier);
+        let limits = SetLimitsParams {
+            max_stake: max_stake.filter(|stake| *stake <= daily).map(Amount::from_micro_ccd),
+            max_bets_per_hour,
+            daily_time_limit: time_limit_minutes.map(Duration::from_minutes),
+            ..limits_params(daily, daily + monthly_extra)
+        };
+        set_limits_with(&mut chain, &init, BOB, BOB_ADDR, limits);
+        set_platform_limits(&mut chain, &init, BOB, "casino", casino_daily, None);
+        let category_limits = SetCategoryLimitsParams {
+            category: GameCategory::Slots,
+            daily_limit: slots_daily.map(Amount::from_micro_ccd),
+            monthly_limit: None,
+        };
+        update_as(&mut chain, &init, BOB, "set_category_limits", &category_limits)
+            .expect("Category limits should be set");
+        
+        let threshold = SetRiskThresholdParams {
+            threshold: review_threshold,
+        };
+        update_as(&mut chain, &init, ALICE, "set_risk_review_threshold", &threshold)
+            .expect("Admin should set the review threshold");
+        let threshold = SetAffordabilityThresholdParams {
+            threshold: affordability_threshold.map(Amount::from_micro_ccd),
+        };
+        update_as(&mut chain, &init, ALICE, "set_affordability_threshold", &threshold)
+            .expect("Admin should set the affordability threshold");
+        
+        for step in steps {
+            match step {
+                SessionStep::Bet { amount, platform, category } => {
+                    let (status, outcome) = check_then_record(
+                        &mut chain,
+                        &init,
+                        amount,
+                        SESSION_PLATFORMS[platform],
+                        SESSION_CATEGORIES[category],
+                    );
+                    prop_assert_eq!(outcome_for_status(&status), outcome);
+                }
+                SessionStep::Wait { minutes } => {
+                    chain
+                        .tick_block_time(Duration::from_minutes(minutes))
+                        .expect("Block time should not overflow");
+                }
+                SessionStep::SelfExclude { days } => {
+                    update_as(&mut chain, &init, BOB, "self_exclude", &SelfExcludeParams {
+                        duration_days: days,
+                    })
+                    .expect("Self-exclusion should succeed");
+                }
+                SessionStep::ExcludePlatform { platform, days } => {
+                    let params = SelfExcludePlatformsParams {
+                        platform_ids: vec![SESSION_PLATFORMS[platform].to_string()],
+                        duration_days: days,
+                    };
+                    update_as(&mut chain, &init, BOB, "self_exclude_platforms", &params)
+                        .expect("Platform exclusion should succeed");
+                }
+                // A session may already be open or closed, so either answer is fine
+                SessionStep::StartSession { platform } => {
+                    let _ = update_session(&mut chain, &init, "start_session", BOB, SESSION_PLATFORMS[platform]);
+                }
+                SessionStep::EndSession { platform } => {
+                    let _ = update_session(&mut chain, &init, "end_session", BOB, SESSION_PLATFORMS[platform]);
+                }
+                SessionStep::Pause { transactions } => {
+                    let pause = PauseFlags {
+                        transactions,
+                        ..PauseFlags::default()
+                    };
+                    update_as(&mut chain, &init, ALICE, "set_paused", &pause)
+                        .expect("Admin should pause");
+                }
+            }
+        }
+    }
+}
+
+// ============================================================================
+// TESTS - EXCLUSION PRUNING
+// ============================================================================
+
+// Test 50: Expired exclusions drop out of the views straight away, and anyone
+// can prune them from state in bounded batches without touching ones in force
+#[test]
+fn test_prune_expired_exclusions() {
+    let verifier = TestVerifier::new();
+    let (mut chain, init) = initialize_with_verifier(&verifier);
+    
+    register_user_with_age_verification(&mut chain, &init, ALICE, ALICE_ADDR, &verifier);
+    register_user_with_age_verification(&mut chain, &init, BOB, BOB_ADDR, &verifier);
+    
+    update_as(&mut chain, &init, ALICE, "self_exclude", &SelfExcludeParams {
+        duration_days: 1,
+    })
+    .expect("Self-exclusion should succeed");
+    let platforms = SelfExcludePlatformsParams {
+        platform_ids: vec!["casino".to_string()],
+        duration_days: 3,
+    };
+    update_as(&mut chain, &init, ALICE, "self_exclude_platforms", &platforms)
+        .expect("Platform exclusion should succeed");
+    
+    let regulator = RoleParams {
+        account: CHARLIE,
+        role: Role::Regulator,
+    };
+    update_as(&mut chain, &init, ALICE, "grant_role", &regulator)
+        .expect("Admin should grant the regulator role");
+    let exclusion = ImposeExclusionParams {
+        user_account: BOB,
+        duration_days: None,
+        reason: ExclusionReason::CourtOrder,
+    };
+    update_as(&mut chain, &init, CHARLIE, "impose_exclusion", &exclusion)
+        .expect("Regulator exclusion should succeed");
+    
+    chain
+        .tick_block_time(Duration::from_days(2))
+        .expect("Block time should not overflow");
+    
+    // ALICE's cooldown has ended but her casino exclusion has not
+    let active_exclusions = |chain: &Chain, account: AccountAddress| -> ActiveExclusions {
+        chain
+            .contract_invoke(
+                CHARLIE,
+                Address::Account(CHARLIE),
+                Energy::from(10_000),
+                UpdateContractPayload {
+                    address: init.contract_address,
+                    amount: Amount::zero(),
+                    receive_name: OwnedReceiveName::new_unchecked(
+                        "safestake_registry.get_active_exclusions".to_string()
+                    ),
+                    message: OwnedParameter::from_serial(&account)
+                        .expect("Parameter within size bounds"),
+                },
+            )
+            .expect("Invoke should succeed")
+            .parse_return_value()
+            .expect("Should return ActiveExclusions")
+    };
+    let alice = active_exclusions(&chain, ALICE);
+    assert_eq!(alice.global, None);
+    assert_eq!(alice.platforms.len(), 1);
+    assert_eq!(alice.platforms[0].0, "casino");
+    assert!(alice.categories.is_empty());
+    
+    let bob = active_exclusions(&chain, BOB);
+    assert_eq!(
+        bob.global,
+        Some((
+            None,
+            Some(ExclusionSource {
+                issuer: ExclusionIssuer::Regulator(CHARLIE),
+                reason: ExclusionReason::CourtOrder,
+            })
+        ))
+    );
+    
+    // Only ALICE had anything expired, and pruning again finds nothing left
+    let prune = PruneExclusionsParams {
+        users: vec![ALICE, BOB],
+    };
+    let pruned: u32 = update_as(&mut chain, &init, CHARLIE, "prune_expired_exclusions", &prune)
+        .expect("Anyone may prune expired exclusions")
+        .parse_return_value()
+        .expect("Should return u32");
+    assert_eq!(pruned, 1);
+    let pruned: u32 = update_as(&mut chain, &init, CHARLIE, "prune_expired_exclusions", &prune)
+        .expect("Pruning again should succeed")
+        .parse_return_value()
+        .expect("Should return u32");
+    assert_eq!(pruned, 0);
+    
+    assert_eq!(active_exclusions(&chain, ALICE), alice);
+    assert_eq!(
+        try_record_transaction(&mut chain, &init, BOB, 100_000_000, "sportsbook"),
+        TransactionOutcome::SelfExcluded
+    );
+    
+    // Work per call is bounded
+    let too_many = PruneExclusionsParams {
+        users: vec![ALICE; 101],
+    };
+    let error: ContractError = update_as(&mut chain, &init, CHARLIE, "prune_expired_exclusions", &too_many)
+        .expect_err("Should fail - too many users in one call")
+        .parse_return_value()
+        .expect("Should return ContractError");
+    assert_eq!(error, ContractError::BatchTooLarge);
+}
+
+// ============================================================================
+// TESTS - REACTIVATION
+// ============================================================================
+
+// Test 51: Betting does not resume when a self-exclusion ends, only 24 hours
+// after the user asks to come back
+#[test]
+fn test_reactivation_after_self_exclusion() {
+    let verifier = TestVerifier::new();
+    let (mut chain, init) = initialize_with_verifier(&verifier);
+    
+    register_user_with_age_verification(&mut chain, &init, ALICE, ALICE_ADDR, &verifier);
+    register_user_with_age_verification(&mut chain, &init, BOB, BOB_ADDR, &verifier);
+    set_limits(&mut chain, &init, ALICE, ALICE_ADDR, 1_000_000_000, 5_000_000_000);
+    
+    let error: ContractError = update_as(&mut chain, &init, BOB, "request_reactivation", &())
+        .expect_err("Should fail - BOB never excluded himself")
+        .parse_return_value()
+        .expect("Should return ContractError");
+    assert_eq!(error, ContractError::ReactivationNotRequired);
+    
+    update_as(&mut chain, &init, ALICE, "self_exclude", &SelfExcludeParams { duration_days: 1 })
+        .expect("Self-exclusion should succeed");
+    let error: ContractError = update_as(&mut chain, &init, ALICE, "request_reactivation", &())
+        .expect_err("Should fail - the exclusion has not ended yet")
+        .parse_return_value()
+        .expect("Should return ContractError");
+    assert_eq!(error, ContractError::OnCooldown);
+    
+    // The exclusion has ended, but ALICE has not asked to come back
+    chain
+        .tick_block_time(Duration::from_days(1))
+        .expect("Block time should not overflow");
+    let details = check_eligibility_details(&chain, &init, ALICE, 100_000_000);
+    assert_eq!(details.status, EligibilityStatus::PendingReactivation);
+    assert_eq!(details.blocked_until, None);
+    assert_eq!(
+        try_record_transaction(&mut chain, &init, ALICE, 100_000_000, "casino"),
+        TransactionOutcome::PendingReactivation
+    );
+    
+    let update = update_as(&mut chain, &init, ALICE, "request_reactivation", &())
+        .expect("Requesting reactivation should succeed");
+    let reactivates_at = chain
+        .block_time()
+        .checked_add(Duration::from_hours(24))
+        .expect("Block time should not overflow");
+    assert_eq!(
+        contract_events(&update),
+        vec![Event::ReactivationRequested(ReactivationEvent {
+            user_account: ALICE,
+            reactivates_at,
+        })]
+    );
+    
+    // Asking again does not restart the wait
+    chain
+        .tick_block_time(Duration::from_hours(23))
+        .expect("Block time should not overflow");
+    update_as(&mut chain, &init, ALICE, "request_reactivation", &())
+        .expect("Requesting reactivation again should succeed");
+    let details = check_eligibility_details(&chain, &init, ALICE, 100_000_000);
+    assert_eq!(details.status, EligibilityStatus::PendingReactivation);
+    assert_eq!(details.blocked_until, Some(reactivates_at));
+    
+    // The first bet after the wait clears the ended exclusion
+    chain
+        .tick_block_time(Duration::from_hours(1))
+        .expect("Block time should not overflow");
+    let bet = RecordTransactionParams {
+        user_account: ALICE,
+        amount: Amount::from_micro_ccd(100_000_000),
+        platform_commitment: platform_commitment(ALICE, "casino"),
+        category: GameCategory::Sports,
+    };
+    let operator = platform_operator(&mut chain, &init, "casino");
+    let update = update_as(&mut chain, &init, operator, "record_transaction", &bet)
+        .expect("Recording a bet should succeed");
+    let outcome: TransactionOutcome = update
+        .parse_return_value()
+        .expect("Should return TransactionOutcome");
+    assert_eq!(outcome, TransactionOutcome::Recorded);
+    assert_eq!(
+        contract_events(&update),
+        vec![Event::ExclusionEnded(ExclusionEndedEvent {
+            user_account: ALICE,
+            scope: ExclusionScope::Global,
+            reactivation_required: true,
+        })]
+    );
+}
+
+// ============================================================================
+// TESTS - PLATFORM PRIVACY
+// ============================================================================
+
+// View a user's platform history as the given account
+fn platform_history(
+    chain: &Chain,
+    init: &ContractInitSuccess,
+    sender: AccountAddress,
+    user_account: AccountAddress,
+) -> Result<PlatformHistory, ContractError> {
+    chain
+        .contract_invoke(
+            sender,
+            Address::Account(sender),
+            Energy::from(10_000),
+            UpdateContractPayload {
+                address: init.contract_address,
+                amount: Amount::zero(),
+                receive_name: OwnedReceiveName::new_unchecked(
+                    "safestake_registry.get_platform_history".to_string()
+                ),
+                message: OwnedParameter::from_serial(&user_account)
+                    .expect("Parameter within size bounds"),
+            },
+        )
+        .map(|invoke| invoke.parse_return_value().expect("Should return PlatformHistory"))
+        .map_err(|error| error.parse_return_value().expect("Should return ContractError"))
+}
+
+// Test 52: Platforms are stored as commitments under the user's own salt, which
+// the user and auditors they share it with can open, while the count of
+// distinct platforms is kept
+#[test]
+fn test_platform_history_is_committed() {
+    let verifier = TestVerifier::new();
+    let (mut chain, init) = initialize_with_verifier(&verifier);
+    
+    register_user_with_age_verification(&mut chain, &init, ALICE, ALICE_ADDR, &verifier);
+    register_user_with_age_verification(&mut chain, &init, BOB, BOB_ADDR, &verifier);
+    set_limits(&mut chain, &init, ALICE, ALICE_ADDR, 1_000_000_000, 5_000_000_000);
+    set_limits(&mut chain, &init, BOB, BOB_ADDR, 1_000_000_000, 5_000_000_000);
+    
+    record_transaction(&mut chain, &init, ALICE, 100_000_000, "casino");
+    record_transaction(&mut chain, &init, ALICE, 100_000_000, "casino");
+    record_transaction(&mut chain, &init, ALICE, 100_000_000, "sportsbook");
+    record_transaction(&mut chain, &init, BOB, 100_000_000, "casino");
+    
+    let alice = platform_history(&chain, &init, ALICE, ALICE).expect("ALICE may view her history");
+    assert_eq!(alice.count, 2);
+    assert_eq!(alice.commitments.len(), 2);
+    assert!(alice.commitments.contains(&platform_commitment(ALICE, "casino")));
+    assert!(alice.commitments.contains(&platform_commitment(ALICE, "sportsbook")));
+    assert!(!alice.commitments.contains(&platform_commitment(ALICE, "lottery")));
+    
+    // The same platform gives a different commitment for another user
+    let bob = platform_history(&chain, &init, BOB, BOB).expect("BOB may view his history");
+    assert_eq!(bob.count, 1);
+    assert_eq!(bob.commitments, vec![platform_commitment(BOB, "casino")]);
+    assert!(!alice.commitments.contains(&bob.commitments[0]));
+    
+    assert_eq!(
+        platform_history(&chain, &init, CHARLIE, ALICE),
+        Err(ContractError::Unauthorized)
+    );
+    let auditor = RoleParams {
+        account: CHARLIE,
+        role: Role::Auditor,
+    };
+    update_as(&mut chain, &init, ALICE, "grant_role", &auditor)
+        .expect("Admin should grant the auditor role");
+    assert_eq!(platform_history(&chain, &init, CHARLIE, ALICE), Ok(alice));
+}
+
+// ============================================================================
+// TESTS - SESSION OPERATORS
+// ============================================================================
+
+// Test 53: Only a platform's registered operator can start or end sessions on it
+#[test]
+fn test_sessions_reported_only_by_platform_operator() {
+    let verifier = TestVerifier::new();
+    let (mut chain, init) = initialize_with_verifier(&verifier);
+    
+    register_user_with_age_verification(&mut chain, &init, ALICE, ALICE_ADDR, &verifier);
+    let sportsbook = platform_operator(&mut chain, &init, "sportsbook");
+    let session = SessionParams {
+        user_account: ALICE,
+        platform_id: "casino".to_string(),
+    };
+    
+    for sender in [BOB, sportsbook] {
+        let error: ContractError = update_as(&mut chain, &init, sender, "start_session", &session)
+            .expect_err("Should fail - not the casino's operator")
+            .parse_return_value()
+            .expect("Should return ContractError");
+        assert_eq!(error, ContractError::Unauthorized);
+    }
+    
+    update_session(&mut chain, &init, "start_session", ALICE, "casino")
+        .expect("The casino's operator should start the session");
+    let error: ContractError = update_as(&mut chain, &init, BOB, "end_session", &session)
+        .expect_err("Should fail - not the casino's operator")
+        .parse_return_value()
+        .expect("Should return ContractError");
+    assert_eq!(error, ContractError::Unauthorized);
+    
+    let play_time = get_play_time(&chain, &init, ALICE);
+    assert_eq!(play_time.active_sessions.len(), 1);
+    update_session(&mut chain, &init, "end_session", ALICE, "casino")
+        .expect("The casino's operator should end the session");
+}
+
+// ============================================================================
+// TESTS - RE-REGISTRATION
+// ============================================================================
+
+// Test 54: Replaying the verifier's signature does not reset a registered user,
+// so an operator's exclusion stays in place
+#[test]
+fn test_reregistration_keeps_operator_exclusion() {
+    let verifier = TestVerifier::new();
+    let (mut chain, init) = initialize_with_verifier(&verifier);
+    
+    register_user_with_age_verification(&mut chain, &init, ALICE, ALICE_ADDR, &verifier);
+    set_limits(&mut chain, &init, ALICE, ALICE_ADDR, 1_000_000_000, 5_000_000_000);
+    
+    let operator = SetOperatorParams {
+        operator: BOB,
+        platform_id: Some("casino".to_string()),
+    };
+    update_as(&mut chain, &init, ALICE, "set_operator", &operator)
+        .expect("Admin should register the operator");
+    let exclusion = ImposePlatformExclusionParams {
+        user_account: ALICE,
+        duration_days: 30,
+        reason: ExclusionReason::HarmMarkers,
+    };
+    update_as(&mut chain, &init, BOB, "impose_platform_exclusion", &exclusion)
+        .expect("Operator exclusion should succeed");
+    
+    let registration = RegisterUserParams {
+        account: ALICE,
+        signature: verifier.sign_account(ALICE),
+    };
+    let error: ContractError = update_as(&mut chain, &init, ALICE, "register_user", &registration)
+        .expect_err("Should fail - ALICE is already registered")
+        .parse_return_value()
+        .expect("Should return ContractError");
+    assert_eq!(error, ContractError::UserAlreadyRegistered);
+    
+    assert_eq!(
+        try_record_transaction(&mut chain, &init, ALICE, 100_000_000, "casino"),
+        TransactionOutcome::PlatformExcluded
+    );
+}
+
+// ============================================================================
+// TESTS - TRUSTED CONTACT LIMITS
+// ============================================================================
+
+// Test 55: Trusted contacts cannot chain cooldowns to keep a user excluded past
+// the maximum they consented to
+#[test]
+fn test_trusted_contacts_cannot_chain_cooldowns() {
+    let verifier = TestVerifier::new();
+    let (mut chain, init) = initialize_with_verifier(&verifier);
+    
+    register_user_with_age_verification(&mut chain, &init, ALICE, ALICE_ADDR, &verifier);
+    set_limits(&mut chain, &init, ALICE, ALICE_ADDR, 1_000_000_000, 5_000_000_000);
+    for contact in [BOB, CHARLIE] {
+        let nomination = SetTrustedContactParams {
+            contact,
+            max_cooldown_days: Some(3),
+        };
+        update_as(&mut chain, &init, ALICE, "set_trusted_contact", &nomination)
+            .expect("Nominating a trusted contact should succeed");
+    }
+    
+    let cooldown = TrustedCooldownParams {
+        user_account: ALICE,
+        duration_days: 3,
+    };
+    update_as(&mut chain, &init, BOB, "request_cooldown", &cooldown)
+        .expect("Trusted contact cooldown should succeed");
+    let until = chain.block_time().checked_add(Duration::from_days(3));
+    
+    // Neither the same contact nor another one can extend it before it ends
+    chain
+        .tick_block_time(Duration::from_days(2))
+        .expect("Block time should not overflow");
+    for contact in [BOB, CHARLIE] {
+        let error: ContractError = update_as(&mut chain, &init, contact, "request_cooldown", &cooldown)
+            .expect_err("Should fail - a contact's cooldown is still running")
+            .parse_return_value()
+            .expect("Should return ContractError");
+        assert_eq!(error, ContractError::CooldownTooLong);
+    }
+    
+    let details = check_eligibility_details(&chain, &init, ALICE, 100_000_000);
+    assert_eq!(details.status, EligibilityStatus::OnCooldown);
+    assert_eq!(details.blocked_until, until);
+    
+    chain
+        .tick_block_time(Duration::from_days(1))
+        .expect("Block time should not overflow");
+    record_transaction(&mut chain, &init, ALICE, 100_000_000, "casino");
+}
+
+// ============================================================================
+// TESTS - ATTESTATION REPLAY
+// ============================================================================
+
+// Test 56: An attestation only counts on the contract instance it was signed
+// for, and a bare account and expiry signature is not accepted
+#[test]
+fn test_attestation_bound_to_contract_instance() {
+    let verifier = TestVerifier::new();
+    let (mut chain, init) = initialize_with_verifier(&verifier);
+    
+    register_user_with_age_verification(&mut chain, &init, ALICE, ALICE_ADDR, &verifier);
+    let valid_until = chain
+        .block_time()
+        .checked_add(Duration::from_days(90))
+        .expect("Timestamp should not overflow");
+    
+    let other_instance = ContractAddress::new(init.contract_address.index + 1, 0);
+    let mut bare_message = ALICE.0.to_vec();
+    bare_message.extend_from_slice(&valid_until.timestamp_millis().to_le_bytes());
+    for message in [attestation_message(other_instance, ALICE, 0, valid_until), bare_message] {
+        let attestation = AffordabilityAttestationParams {
+            account: ALICE,
+            nonce: 0,
+            valid_until,
+            signature: SignatureEd25519(verifier.signing_key.sign(&message).to_bytes()),
+        };
+        let error: ContractError =
+            update_as(&mut chain, &init, BOB, "submit_affordability_attestation", &attestation)
+                .expect_err("Should fail - not signed for this contract")
+                .parse_return_value()
+                .expect("Should return ContractError");
+        assert_eq!(error, ContractError::InvalidSignature);
+    }
+}
+
+// ============================================================================
+// TESTS - OVERFLOW IN BATCHES
+// ============================================================================
+
+// Test 57: A bet too large to add up is rejected on its own inside a batch and
+// counted as a rejected attempt, while the rest of the batch is recorded
+#[test]
+fn test_overflowing_bet_in_batch_is_rejected_alone() {
+    let (mut chain, init) = initialize_with_maximum_limits();
+    
+    let entry = |amount: u64, bet_id: u64| BatchTransactionEntry {
+        user_account: ALICE,
+        amount: Amount::from_micro_ccd(amount),
+        platform_commitment: platform_commitment(ALICE, "casino"),
+        category: GameCategory::Casino,
+        bet_id,
+    };
+    let results = record_transactions_batch(
+        &mut chain,
+        &init,
+        "casino",
+        vec![entry(100_000_000, 1), entry(u64::MAX, 2), entry(200_000_000, 3)],
+    );
+    let outcomes: Vec<TransactionOutcome> = results.iter().map(|result| result.outcome).collect();
+    assert_eq!(
+        outcomes,
+        vec![
+            TransactionOutcome::Recorded,
+            TransactionOutcome::DailyLimitExceeded,
+            TransactionOutcome::Recorded,
+        ]
+    );
+    
+    let details = check_eligibility_details(&chain, &init, ALICE, 0);
+    assert_eq!(details.remaining_daily.micro_ccd, u64::MAX - 300_000_000);
+    
+    let attempts: RejectedAttemptsInfo = chain
+        .contract_invoke(
+            ALICE,
+            ALICE_ADDR,
+            Energy::from(10_000),
+            UpdateContractPayload {
+                address: init.contract_address,
+                amount: Amount::zero(),
+                receive_name: OwnedReceiveName::new_unchecked(
+                    "safestake_registry.get_rejected_attempts".to_string()
+                ),
+                message: OwnedParameter::from_serial(&ALICE)
+                    .expect("Parameter within size bounds"),
+            },
+        )
+        .expect("Invoke should succeed")
+        .parse_return_value()
+        .expect("Should return RejectedAttemptsInfo");
+    assert_eq!(attempts.today, 1);
+}
+
+// ============================================================================
+// TESTS - REACTIVATION AFTER RE-REGISTRATION
+// ============================================================================
+
+// Test 58: Registering again after a self-exclusion ends is refused, so it
+// cannot skip the reactivation step
+#[test]
+fn test_reregistration_keeps_reactivation_required() {
+    let verifier = TestVerifier::new();
+    let (mut chain, init) = initialize_with_verifier(&verifier);
+    
+    register_user_with_age_verification(&mut chain, &init, ALICE, ALICE_ADDR, &verifier);
+    set_limits(&mut chain, &init, ALICE, ALICE_ADDR, 1_000_000_000, 5_000_000_000);
+    update_as(&mut chain, &init, ALICE, "self_exclude", &SelfExcludeParams { duration_days: 1 })
+        .expect("Self-exclusion should succeed");
+    chain
+        .tick_block_time(Duration::from_days(1))
+        .expect("Block time should not overflow");
+    
+    let registration = RegisterUserParams {
+        account: ALICE,
+        signature: verifier.sign_account(ALICE),
+    };
+    let error: ContractError = update_as(&mut chain, &init, ALICE, "register_user", &registration)
+        .expect_err("Should fail - ALICE is already registered")
+        .parse_return_value()
+        .expect("Should return ContractError");
+    assert_eq!(error, ContractError::UserAlreadyRegistered);
+    
+    assert_eq!(
+        check_eligibility(&chain, &init, ALICE, 100_000_000),
+        EligibilityStatus::PendingReactivation
+    );
+    assert_eq!(
+        try_record_transaction(&mut chain, &init, ALICE, 100_000_000, "casino"),
+        TransactionOutcome::PendingReactivation
+    );
+}
+
+// ============================================================================
+// TESTS - UPGRADES FROM OLDER LAYOUTS
+// ============================================================================
+
+// Module built from the first release with a state version tag (layout 1)
+const MODULE_STATE_V1: &str = "./tests/fixtures/safestake_registry_state_v1.wasm.v1";
+
+// Test 59: A contract deployed with the layout 1 module keeps its users,
+// limits, exclusions, platform history and regulators after upgrading to the
+// current module and migrating. Users are converted in batches, and until
+// they all are, nothing can change a user or upgrade again
+#[test]
+fn test_upgrade_from_state_v1() {
+    let verifier = TestVerifier::new();
+    let (mut chain, init) = initialize_module(&verifier, MODULE_STATE_V1);
+    
+    register_user_with_age_verification(&mut chain, &init, ALICE, ALICE_ADDR, &verifier);
+    register_user_with_age_verification(&mut chain, &init, BOB, BOB_ADDR, &verifier);
+    set_limits(&mut chain, &init, BOB, BOB_ADDR, 1_000_000, 10_000_000);
+    // Layout 1 bets carried no platform commitment
+    let bet = (BOB, Amount::from_micro_ccd(400_000), "casino".to_string(), GameCategory::Sports);
+    update_as(&mut chain, &init, ALICE, "record_transaction", &bet)
+        .expect("Recording a bet should succeed");
+    update_as(&mut chain, &init, ALICE, "self_exclude", &SelfExcludeParams { duration_days: 7 })
+        .expect("Self-exclusion should succeed");
+    let platforms = SelfExcludePlatformsParams {
+        platform_ids: vec!["sportsbook".to_string()],
+        duration_days: 7,
+    };
+    update_as(&mut chain, &init, BOB, "self_exclude_platforms", &platforms)
+        .expect("Platform exclusion should succeed");
+    // Layout 1 kept regulators in a set of their own, set with (regulator, enabled)
+    update_as(&mut chain, &init, ALICE, "set_regulator", &(CHARLIE, true))
+        .expect("Admin should add the regulator");
+    
+    let module = module_load_v1("./concordium-out/module.wasm.v1").expect("Module exists at path");
+    let deployment = chain
+        .module_deploy_v1(SIGNER, ALICE, module)
+        .expect("Deploy valid module");
+    let params = UpgradeParams {
+        module: deployment.module_reference,
+        migrate: Some((
+            OwnedEntrypointName::new_unchecked("migrate".to_string()),
+            OwnedParameter::empty(),
+        )),
+    };
+    update_as(&mut chain, &init, ALICE, "upgrade", &params).expect("Admin should upgrade");
+    
+    let version: u32 = chain
+        .contract_invoke(
+            ALICE,
+            ALICE_ADDR,
+            Energy::from(10_000),
+            UpdateContractPayload {
+                address: init.contract_address,
+                amount: Amount::zero(),
+                receive_name: OwnedReceiveName::new_unchecked(
+                    "safestake_registry.get_state_version".to_string()
+                ),
+                message: OwnedParameter::empty(),
+            },
+        )
+        .expect("Invoke should succeed")
+        .parse_return_value()
+        .expect("Should return u32");
+    assert_eq!(version, 11);
+    
+    assert_eq!(check_eligibility(&chain, &init, BOB, 100), EligibilityStatus::NotRegistered);
+    let error: ContractError = update_as(&mut chain, &init, BOB, "set_limits", &limits_params(100, 1_000))
+        .expect_err("Should fail - users are still being migrated")
+        .parse_return_value()
+        .expect("Should return ContractError");
+    assert_eq!(error, ContractError::MigrationPending);
+    let error: ContractError = update_as(&mut chain, &init, ALICE, "upgrade", &params)
+        .expect_err("Should fail - users are still being migrated")
+        .parse_return_value()
+        .expect("Should return ContractError");
+    assert_eq!(error, ContractError::MigrationPending);
+    let error: ContractError = update_as(&mut chain, &init, CHARLIE, "migrate_users", &501u32)
+        .expect_err("Should fail - too many users in one call")
+        .parse_return_value()
+        .expect("Should return ContractError");
+    assert_eq!(error, ContractError::BatchTooLarge);
+    
+    // Anyone may convert the users, one at a time here
+    assert!(migrate_users(&mut chain, &init, 1));
+    assert!(!migrate_users(&mut chain, &init, 1));
+    assert!(!migrate_users(&mut chain, &init, 1));
+    
+    // Users, limits, spending and exclusions survive
+    assert_eq!(check_eligibility(&chain, &init, ALICE, 100), EligibilityStatus::OnCooldown);
+    let details = check_eligibility_details(&chain, &init, BOB, 100);
+    assert_eq!(details.status, EligibilityStatus::Eligible);
+    assert_eq!(details.remaining_daily, Amount::from_micro_ccd(600_000));
+    assert_eq!(
+        try_record_transaction(&mut chain, &init, BOB, 100, "sportsbook"),
+        TransactionOutcome::PlatformExcluded
+    );
+    
+    // The plain-text platform history is dropped, as only BOB's wallet holds the
+    // salt to commit it with, and only its count is kept
+    let history = platform_history(&chain, &init, BOB, BOB).expect("BOB may view his history");
+    assert_eq!(history.count, 1);
+    assert!(history.commitments.is_empty());
+    
+    // Nothing is paused, and the regulator kept their powers as a role
+    record_transaction(&mut chain, &init, BOB, 500_000, "casino");
+    let exclusion = ImposeExclusionParams {
+        user_account: BOB,
+        duration_days: None,
+        reason: ExclusionReason::CourtOrder,
+    };
+    update_as(&mut chain, &init, CHARLIE, "impose_exclusion", &exclusion)
+        .expect("Regulator exclusion should succeed");
+    assert_eq!(check_eligibility(&chain, &init, BOB, 100), EligibilityStatus::SelfExcluded);
+    
+    // Upgrading again finds the state up to date
+    update_as(&mut chain, &init, ALICE, "upgrade", &params).expect("Admin should upgrade again");
+    assert_eq!(check_eligibility(&chain, &init, BOB, 100), EligibilityStatus::SelfExcluded);
+}
+
+// ============================================================================
+// TESTS - RISK INDICATORS
+// ============================================================================
+
+// Test 60: Zero-amount bets leave the risk indicators alone, and each platform
+// counts once a day however its bets are spread out
+#[test]
+fn test_risk_indicators_ignore_zero_bets_and_repeat_platforms() {
+    let verifier = TestVerifier::new();
+    let (mut chain, init) = initialize_with_verifier(&verifier);
+    
+    register_user_with_age_verification(&mut chain, &init, ALICE, ALICE_ADDR, &verifier);
+    set_limits(&mut chain, &init, ALICE, ALICE_ADDR, 1_000_000_000, 5_000_000_000);
+    
+    // Midday, so none of the bets count as late-night activity
+    chain
+        .tick_block_time(Duration::from_hours(12))
+        .expect("Block time should not overflow");
+    
+    let risk_profile = |chain: &Chain| -> RiskProfile {
+        chain
+            .contract_invoke(
+                ALICE,
+                ALICE_ADDR,
+                Energy::from(10_000),
+                UpdateContractPayload {
+                    address: init.contract_address,
+                    amount: Amount::zero(),
+                    receive_name: OwnedReceiveName::new_unchecked(
+                        "safestake_registry.get_risk_profile".to_string()
+                    ),
+                    message: OwnedParameter::from_serial(&ALICE)
+                        .expect("Parameter within size bounds"),
+                },
+            )
+            .expect("Invoke should succeed")
+            .parse_return_value()
+            .expect("Should return RiskProfile")
+    };
+    
+    for platform_id in ["casino_a", "casino_b", "casino_c"] {
+        record_transaction(&mut chain, &init, ALICE, 0, platform_id);
+    }
+    assert_eq!(risk_profile(&chain).platforms_today, 0);
+    
+    record_transaction(&mut chain, &init, ALICE, 10_000_000, "casino_a");
+    record_transaction(&mut chain, &init, ALICE, 0, "casino_a");
+    record_transaction(&mut chain, &init, ALICE, 20_000_000, "casino_a");
+    let risk = risk_profile(&chain);
+    assert_eq!(risk.platforms_today, 1);
+    assert_eq!(risk.stake_increases, 1);
+    assert_eq!(risk.last_stake, Amount::from_micro_ccd(20_000_000));
+    
+    // A new day starts the count again
+    chain
+        .tick_block_time(Duration::from_days(1))
+        .expect("Block time should not overflow");
+    record_transaction(&mut chain, &init, ALICE, 30_000_000, "casino_a");
+    record_transaction(&mut chain, &init, ALICE, 40_000_000, "casino_b");
+    assert_eq!(risk_profile(&chain).platforms_today, 2);
+}
+
+// ============================================================================
+// TESTS - PLATFORM ACCOUNTS ONLY WHERE LIMITS ARE SET
+// ============================================================================
+
+// Test 61: Bets on a platform without limits leave no account for it, so its
+// spending only counts from when the user sets limits there: after spending
+// 50 CCD on the casino today, a 20 CCD/day casino limit still allows 20 CCD
+// more, while the global limit keeps counting everything. Clearing the limits
+// removes the account again.
+#[test]
+fn test_platform_accounts_only_where_limits_are_set() {
+    let verifier = TestVerifier::new();
+    let (mut chain, init) = initialize_with_verifier(&verifier);
+    
+    register_user_with_age_verification(&mut chain, &init, ALICE, ALICE_ADDR, &verifier);
+    set_limits(&mut chain, &init, ALICE, ALICE_ADDR, 100_000_000_000, 500_000_000_000);
+    record_transaction(&mut chain, &init, ALICE, 50_000_000_000, "casino");
+    
+    let casino_check = CheckEligibilityParams {
+        user_account: ALICE,
+        proposed_amount: Amount::from_micro_ccd(20_000_000_000),
+        platform_id: Some("casino".to_string()),
+        category: None,
+    };
+    set_platform_limits(&mut chain, &init, ALICE, "casino", Some(20_000_000_000), None);
+    let details = check_eligibility_with(&chain, &init, &casino_check);
+    assert_eq!(details.status, EligibilityStatus::Eligible);
+    assert_eq!(details.remaining_daily, Amount::from_micro_ccd(50_000_000_000));
+    assert_eq!(details.max_eligible_stake, Amount::from_micro_ccd(20_000_000_000));
+    
+    record_transaction(&mut chain, &init, ALICE, 20_000_000_000, "casino");
+    let details = check_eligibility_with(&chain, &init, &casino_check);
+    assert_eq!(details.status, EligibilityStatus::PlatformDailyLimitReached);
+    assert_eq!(details.remaining_daily, Amount::from_micro_ccd(30_000_000_000));
+    
+    set_platform_limits(&mut chain, &init, ALICE, "casino", None, None);
+    let details = check_eligibility_with(&chain, &init, &casino_check);
+    assert_eq!(details.status, EligibilityStatus::Eligible);
+    set_platform_limits(&mut chain, &init, ALICE, "casino", Some(20_000_000_000), None);
+    let details = check_eligibility_with(&chain, &init, &casino_check);
+    assert_eq!(details.status, EligibilityStatus::Eligible);
+}
+
+// ============================================================================
+// TESTS - BETS FROM REGISTERED OPERATORS
+// ============================================================================
+
+// Test 62: Only registered operators record bets, and always on their own
+// platform, so an excluded platform cannot record under another ID
+#[test]
+fn test_bets_recorded_only_by_platform_operator() {
+    let verifier = TestVerifier::new();
+    let (mut chain, init) = initialize_with_verifier(&verifier);
+    
+    register_user_with_age_verification(&mut chain, &init, ALICE, ALICE_ADDR, &verifier);
+    set_limits(&mut chain, &init, ALICE, ALICE_ADDR, 1_000_000_000, 5_000_000_000);
+    let platforms = SelfExcludePlatformsParams {
+        platform_ids: vec!["casino".to_string()],
+        duration_days: 7,
+    };
+    update_as(&mut chain, &init, ALICE, "self_exclude_platforms", &platforms)
+        .expect("Platform exclusion should succeed");
+    
+    let bet = RecordTransactionParams {
+        user_account: ALICE,
+        amount: Amount::from_micro_ccd(100_000_000),
+        platform_commitment: platform_commitment(ALICE, "casino"),
+        category: GameCategory::Casino,
+    };
+    let batch = RecordTransactionsBatchParams {
+        entries: vec![BatchTransactionEntry {
+            user_account: ALICE,
+            amount: Amount::from_micro_ccd(100_000_000),
+            platform_commitment: platform_commitment(ALICE, "casino"),
+            category: GameCategory::Casino,
+            bet_id: 1,
+        }],
+    };
+    let errors: Vec<ContractError> = [
+        update_as(&mut chain, &init, BOB, "record_transaction", &bet),
+        update_as(&mut chain, &init, BOB, "record_transactions_batch", &batch),
+    ]
+    .into_iter()
+    .map(|result| {
+        result
+            .expect_err("Should fail - BOB is not an operator")
+            .parse_return_value()
+            .expect("Should return ContractError")
+    })
+    .collect();
+    assert_eq!(errors, vec![ContractError::Unauthorized, ContractError::Unauthorized]);
+    
+    assert_eq!(
+        try_record_transaction(&mut chain, &init, ALICE, 100_000_000, "casino"),
+        TransactionOutcome::PlatformExcluded
+    );
+    assert_eq!(
+        try_record_transaction(&mut chain, &init, ALICE, 100_000_000, "sportsbook"),
+        TransactionOutcome::Recorded
+    );
+}
+
+// Test 63: Someone who is not an operator cannot put a user on their
+// automatic cooldown or add to their rejected bets by sending bets for them
+#[test]
+fn test_outsider_bets_leave_no_rejections() {
+    let verifier = TestVerifier::new();
+    let (mut chain, init) = initialize_with_verifier(&verifier);
+    
+    register_user_with_age_verification(&mut chain, &init, BOB, BOB_ADDR, &verifier);
+    set_limits(&mut chain, &init, BOB, BOB_ADDR, 1_000_000, 10_000_000);
+    let rule = SetAutoCooldownParams {
+        rule: Some(AutoCooldownRule {
+            cooldown: Duration::from_days(7),
+            on_limit_reached: false,
+            max_rejections: Some(2),
+            rejection_window: Duration::from_hours(1),
+        }),
+    };
+    update_as(&mut chain, &init, BOB, "set_auto_cooldown", &rule)
+        .expect("Setting the rule should succeed");
+    
+    let over_limit = RecordTransactionParams {
+        user_account: BOB,
+        amount: Amount::from_micro_ccd(2_000_000),
+        platform_commitment: platform_commitment(BOB, "casino"),
+        category: GameCategory::Casino,
+    };
+    for _ in 0..2 {
+        let error: ContractError =
+            update_as(&mut chain, &init, CHARLIE, "record_transaction", &over_limit)
+                .expect_err("Should fail - CHARLIE is not an operator")
+                .parse_return_value()
+                .expect("Should return ContractError");
+        assert_eq!(error, ContractError::Unauthorized);
+    }
+    
+    assert_eq!(check_eligibility(&chain, &init, BOB, 100), EligibilityStatus::Eligible);
+    let attempts: RejectedAttemptsInfo = chain
+        .contract_invoke(
+            BOB,
+            BOB_ADDR,
+            Energy::from(10_000),
+            UpdateContractPayload {
+                address: init.contract_address,
+                amount: Amount::zero(),
+                receive_name: OwnedReceiveName::new_unchecked(
+                    "safestake_registry.get_rejected_attempts".to_string()
+                ),
+                message: OwnedParameter::from_serial(&BOB)
+                    .expect("Parameter within size bounds"),
+            },
+        )
+        .expect("Invoke should succeed")
+        .parse_return_value()
+        .expect("Should return RejectedAttemptsInfo");
+    assert_eq!(attempts.today, 0);
+    
+    // The casino's own rejected bets still count towards the rule
+    for _ in 0..2 {
+        assert_eq!(
+            try_record_transaction(&mut chain, &init, BOB, 2_000_000, "casino"),
+            TransactionOutcome::DailyLimitExceeded
+        );
+    }
+    assert_eq!(check_eligibility(&chain, &init, BOB, 100), EligibilityStatus::OnCooldown);
+}
+
+// ============================================================================
+// TESTS - ROLLING AFFORDABILITY WINDOW
+// ============================================================================
+
+// Sign an affordability attestation for the user as the verifier
+fn attestation(
+    verifier: &TestVerifier,
+    init: &ContractInitSuccess,
+    account: AccountAddress,
+    nonce: u64,
+    valid_until: Timestamp,
+) -> AffordabilityAttestationParams {
+    let message = attestation_message(init.contract_address, account, nonce, valid_until);
+    AffordabilityAttestationParams {
+        account,
+        nonce,
+        valid_until,
+        signature: SignatureEd25519(verifier.signing_key.sign(&message).to_bytes()),
+    }
+}
+
+// Test 64: The affordability threshold counts spend over the last 30 days, so
+// spending just under it at the end of a monthly period and again after the
+// monthly reset still needs an attestation
+#[test]
+fn test_affordability_threshold_uses_rolling_30_days() {
+    let verifier = TestVerifier::new();
+    let (mut chain, init) = initialize_with_verifier(&verifier);
+    
+    register_user_with_age_verification(&mut chain, &init, ALICE, ALICE_ADDR, &verifier);
+    set_limits(&mut chain, &init, ALICE, ALICE_ADDR, 2_000_000_000, 10_000_000_000);
+    let threshold = SetAffordabilityThresholdParams {
+        threshold: Some(Amount::from_micro_ccd(1_500_000_000)),
+    };
+    update_as(&mut chain, &init, ALICE, "set_affordability_threshold", &threshold)
+        .expect("Admin should set the threshold");
+    
+    chain
+        .tick_block_time(Duration::from_days(29))
+        .expect("Block time should not overflow");
+    record_transaction(&mut chain, &init, ALICE, 1_400_000_000, "casino");
+    
+    // The monthly period resets on day 30, but the day 29 spend stays in the window
+    chain
+        .tick_block_time(Duration::from_days(1))
+        .expect("Block time should not overflow");
+    assert_eq!(
+        check_eligibility_details(&chain, &init, ALICE, 0).remaining_monthly,
+        Amount::from_micro_ccd(10_000_000_000)
+    );
+    assert_eq!(
+        try_record_transaction(&mut chain, &init, ALICE, 1_400_000_000, "casino"),
+        TransactionOutcome::AffordabilityCheckRequired
+    );
+    record_transaction(&mut chain, &init, ALICE, 100_000_000, "casino");
+    
+    // 30 days after day 29 that spend leaves the window, while day 30's stays
+    chain
+        .tick_block_time(Duration::from_days(28))
+        .expect("Block time should not overflow");
+    assert_eq!(
+        check_eligibility(&chain, &init, ALICE, 1_400_000_000),
+        EligibilityStatus::AffordabilityCheckRequired
+    );
+    chain
+        .tick_block_time(Duration::from_days(1))
+        .expect("Block time should not overflow");
+    let details = check_eligibility_details(&chain, &init, ALICE, 1_400_000_000);
+    assert_eq!(details.status, EligibilityStatus::Eligible);
+    assert_eq!(details.max_eligible_stake, Amount::from_micro_ccd(1_400_000_000));
+}
+
+// Test 65: Each attestation uses the user's next nonce, so an older, longer
+// attestation cannot be submitted again over a newer, shorter one
+#[test]
+fn test_attestation_replay_cannot_extend_expiry() {
+    let verifier = TestVerifier::new();
+    let (mut chain, init) = initialize_with_verifier(&verifier);
+    
+    register_user_with_age_verification(&mut chain, &init, ALICE, ALICE_ADDR, &verifier);
+    set_limits(&mut chain, &init, ALICE, ALICE_ADDR, 2_000_000_000, 10_000_000_000);
+    let threshold = SetAffordabilityThresholdParams {
+        threshold: Some(Amount::from_micro_ccd(1_000_000_000)),
+    };
+    update_as(&mut chain, &init, ALICE, "set_affordability_threshold", &threshold)
+        .expect("Admin should set the threshold");
+    
+    let in_days = |chain: &Chain, days: u64| {
+        chain
+            .block_time()
+            .checked_add(Duration::from_days(days))
+            .expect("Timestamp should not overflow")
+    };
+    let longer = attestation(&verifier, &init, ALICE, 0, in_days(&chain, 90));
+    let shorter = attestation(&verifier, &init, ALICE, 1, in_days(&chain, 10));
+    let error: ContractError =
+        update_as(&mut chain, &init, BOB, "submit_affordability_attestation", &shorter)
+            .expect_err("Should fail - nonce 0 is next")
+            .parse_return_value()
+            .expect("Should return ContractError");
+    assert_eq!(error, ContractError::NonceMismatch);
+    update_as(&mut chain, &init, BOB, "submit_affordability_attestation", &longer)
+        .expect("First attestation should be accepted");
+    update_as(&mut chain, &init, BOB, "submit_affordability_attestation", &shorter)
+        .expect("Newer attestation should be accepted");
+    let error: ContractError =
+        update_as(&mut chain, &init, BOB, "submit_affordability_attestation", &longer)
+            .expect_err("Should fail - the older attestation was used")
+            .parse_return_value()
+            .expect("Should return ContractError");
+    assert_eq!(error, ContractError::NonceMismatch);
+    
+    let nonce: u64 = chain
+        .contract_invoke(
+            ALICE,
+            ALICE_ADDR,
+            Energy::from(10_000),
+            UpdateContractPayload {
+                address: init.contract_address,
+                amount: Amount::zero(),
+                receive_name: OwnedReceiveName::new_unchecked(
+                    "safestake_registry.get_attestation_nonce".to_string()
+                ),
+                message: OwnedParameter::from_serial(&ALICE)
+                    .expect("Parameter within size bounds"),
+            },
+        )
+        .expect("Invoke should succeed")
+        .parse_return_value()
+        .expect("Should return u64");
+    assert_eq!(nonce, 2);
+    
+    // The newer attestation's end stands
+    chain
+        .tick_block_time(Duration::from_days(11))
+        .expect("Block time should not overflow");
+    assert_eq!(
+        check_eligibility(&chain, &init, ALICE, 1_500_000_000),
+        EligibilityStatus::AffordabilityCheckRequired
+    );
+}
+
+// ============================================================================
+// TESTS - UPGRADES FROM LATER LAYOUTS
+// ============================================================================
+
+const MODULE_STATE_V6: &str = "./tests/fixtures/safestake_registry_state_v6.wasm.v1";
+const MODULE_STATE_V8: &str = "./tests/fixtures/safestake_registry_state_v8.wasm.v1";
+
+// Test 66: A contract deployed with the layout 6 module keeps its users'
+// spending, platform exclusions, platform count and pending reactivation
+// after upgrading
+#[test]
+fn test_upgrade_from_state_v6() {
+    let verifier = TestVerifier::new();
+    let (mut chain, init) = initialize_module(&verifier, MODULE_STATE_V6);
+    
+    register_user_with_age_verification(&mut chain, &init, ALICE, ALICE_ADDR, &verifier);
+    register_user_with_age_verification(&mut chain, &init, BOB, BOB_ADDR, &verifier);
+    set_limits(&mut chain, &init, ALICE, ALICE_ADDR, 1_000_000, 10_000_000);
+    set_limits(&mut chain, &init, BOB, BOB_ADDR, 1_000_000, 10_000_000);
+    // Layout 6 bets named the platform in the clear and anyone could record them
+    let bet = (BOB, Amount::from_micro_ccd(400_000), "casino".to_string(), GameCategory::Sports);
+    update_as(&mut chain, &init, ALICE, "record_transaction", &bet)
+        .expect("Recording a bet should succeed");
+    let platforms = SelfExcludePlatformsParams {
+        platform_ids: vec!["sportsbook".to_string()],
+        duration_days: 7,
+    };
+    update_as(&mut chain, &init, BOB, "self_exclude_platforms", &platforms)
+        .expect("Platform exclusion should succeed");
+    
+    // ALICE's exclusion ends and she asks to come back before the upgrade
+    update_as(&mut chain, &init, ALICE, "self_exclude", &SelfExcludeParams { duration_days: 1 })
+        .expect("Self-exclusion should succeed");
+    chain
+        .tick_block_time(Duration::from_days(1))
+        .expect("Block time should not overflow");
+    update_as(&mut chain, &init, ALICE, "request_reactivation", &())
+        .expect("Requesting reactivation should succeed");
+    
+    upgrade_to_current(&mut chain, &init);
+    
+    assert_eq!(
+        check_eligibility(&chain, &init, ALICE, 100),
+        EligibilityStatus::PendingReactivation
+    );
+    chain
+        .tick_block_time(Duration::from_hours(24))
+        .expect("Block time should not overflow");
+    assert_eq!(check_eligibility(&chain, &init, ALICE, 100), EligibilityStatus::Eligible);
+    
+    let details = check_eligibility_details(&chain, &init, BOB, 100);
+    assert_eq!(details.status, EligibilityStatus::Eligible);
+    assert_eq!(details.remaining_monthly, Amount::from_micro_ccd(9_600_000));
+    assert_eq!(
+        try_record_transaction(&mut chain, &init, BOB, 100, "sportsbook"),
+        TransactionOutcome::PlatformExcluded
+    );
+    let history = platform_history(&chain, &init, BOB, BOB).expect("BOB may view his history");
+    assert_eq!(history.count, 1);
+    assert!(history.commitments.is_empty());
+}
+
+// Test 67: A contract deployed with the layout 8 module keeps its users'
+// exclusions, spending, platform limits and platform count, while the
+// committed platform history is dropped
+#[test]
+fn test_upgrade_from_state_v8() {
+    let verifier = TestVerifier::new();
+    let (mut chain, init) = initialize_module(&verifier, MODULE_STATE_V8);
+    
+    register_user_with_age_verification(&mut chain, &init, ALICE, ALICE_ADDR, &verifier);
+    register_user_with_age_verification(&mut chain, &init, BOB, BOB_ADDR, &verifier);
+    set_limits(&mut chain, &init, BOB, BOB_ADDR, 1_000_000, 10_000_000);
+    set_platform_limits(&mut chain, &init, BOB, "casino", Some(500_000), None);
+    for (amount, platform_id) in [(300_000, "casino"), (100_000, "poker")] {
+        let bet = (BOB, Amount::from_micro_ccd(amount), platform_id.to_string(), GameCategory::Sports);
+        update_as(&mut chain, &init, ALICE, "record_transaction", &bet)
+            .expect("Recording a bet should succeed");
+    }
+    update_as(&mut chain, &init, ALICE, "self_exclude", &SelfExcludeParams { duration_days: 7 })
+        .expect("Self-exclusion should succeed");
+    
+    upgrade_to_current(&mut chain, &init);
+    
+    assert_eq!(check_eligibility(&chain, &init, ALICE, 100), EligibilityStatus::OnCooldown);
+    let details = check_eligibility_details(&chain, &init, BOB, 100);
+    assert_eq!(details.remaining_daily, Amount::from_micro_ccd(600_000));
+    
+    // The casino limit still counts the casino bet made before the upgrade
+    assert_eq!(
+        try_record_transaction(&mut chain, &init, BOB, 300_000, "casino"),
+        TransactionOutcome::PlatformDailyLimitExceeded
+    );
+    assert_eq!(
+        try_record_transaction(&mut chain, &init, BOB, 200_000, "casino"),
+        TransactionOutcome::Recorded
+    );
+    
+    // The old history only leaves its count, so the casino is committed and
+    // counted afresh by the bet after the upgrade
+    let history = platform_history(&chain, &init, BOB, BOB).expect("BOB may view his history");
+    assert_eq!(history.count, 3);
+    assert_eq!(history.commitments, vec![platform_commitment(BOB, "casino")]);
+}
+
+// ============================================================================
+// TESTS - BOUNDED EXCLUSION PRUNING
+// ============================================================================
+
+// Test 68: A bet only prunes the expired exclusions it falls under, and one
+// prune call looks at no more than 1,000 platform and category exclusions
+#[test]
+fn test_pruning_visits_bounded_exclusions() {
+    let verifier = TestVerifier::new();
+    let (mut chain, init) = initialize_with_verifier(&verifier);
+    
+    register_user_with_age_verification(&mut chain, &init, ALICE, ALICE_ADDR, &verifier);
+    set_limits(&mut chain, &init, ALICE, ALICE_ADDR, 1_000_000_000, 5_000_000_000);
+    let platforms = SelfExcludePlatformsParams {
+        platform_ids: vec!["casino".to_string(), "poker".to_string()],
+        duration_days: 1,
+    };
+    update_as(&mut chain, &init, ALICE, "self_exclude_platforms", &platforms)
+        .expect("Platform exclusion should succeed");
+    chain
+        .tick_block_time(Duration::from_days(2))
+        .expect("Block time should not overflow");
+    
+    let bet = RecordTransactionParams {
+        user_account: ALICE,
+        amount: Amount::from_micro_ccd(100_000),
+        platform_commitment: platform_commitment(ALICE, "casino"),
+        category: GameCategory::Sports,
+    };
+    let operator = platform_operator(&mut chain, &init, "casino");
+    let update = update_as(&mut chain, &init, operator, "record_transaction", &bet)
+        .expect("Recording a bet should succeed");
+    assert_eq!(
+        contract_events(&update),
+        vec![Event::ExclusionEnded(ExclusionEndedEvent {
+            user_account: ALICE,
+            scope: ExclusionScope::Platform("casino".to_string()),
+            reactivation_required: false,
+        })]
+    );
+    
+    // ALICE excludes herself from another 1,000 platforms, next to the
+    // expired poker exclusion
+    for batch in 0..4 {
+        let platforms = SelfExcludePlatformsParams {
+            platform_ids: (0..250).map(|i| format!("platform-{}", batch * 250 + i)).collect(),
+            duration_days: 1,
+        };
+        chain
+            .contract_update(
+                SIGNER,
+                ALICE,
+                ALICE_ADDR,
+                Energy::from(1_000_000),
+                UpdateContractPayload {
+                    address: init.contract_address,
+                    amount: Amount::zero(),
+                    receive_name: OwnedReceiveName::new_unchecked(
+                        "safestake_registry.self_exclude_platforms".to_string()
+                    ),
+                    message: OwnedParameter::from_serial(&platforms)
+                        .expect("Parameter within size bounds"),
+                },
+            )
+            .expect("Platform exclusion should succeed");
+    }
+    chain
+        .tick_block_time(Duration::from_days(2))
+        .expect("Block time should not overflow");
+    
+    let prune = PruneExclusionsParams {
+        users: vec![ALICE],
+    };
+    let mut prune_events = || -> usize {
+        let update = chain
+            .contract_update(
+                SIGNER,
+                CHARLIE,
+                Address::Account(CHARLIE),
+                Energy::from(3_000_000),
+                UpdateContractPayload {
+                    address: init.contract_address,
+                    amount: Amount::zero(),
+                    receive_name: OwnedReceiveName::new_unchecked(
+                        "safestake_registry.prune_expired_exclusions".to_string()
+                    ),
+                    message: OwnedParameter::from_serial(&prune)
+                        .expect("Parameter within size bounds"),
+                },
+            )
+            .expect("Anyone may prune expired exclusions");
+        contract_events(&update).len()
+    };
+    assert_eq!(prune_events(), 1_000);
+    assert_eq!(prune_events(), 1);
+    assert_eq!(prune_events(), 0);
+}
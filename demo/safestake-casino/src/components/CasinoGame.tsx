@@ -47,8 +47,10 @@ import {
   CONTRACT_SUBINDEX,
   NODE_ADDRESS,
   NODE_PORT,
+  OPERATOR_ACCOUNT,
   VERIFIER_BACKEND_URL,
 } from "../config";
+import { platformCommitment } from "@/lib/platformCommitment";
 
 interface CasinoGameProps {
   connection: any;
@@ -216,6 +218,7 @@ export default function CasinoGame({
       const eligCheck = await sdk.checkEligibility({
         userAccount,
         proposedAmountCCD: amount,
+        category: "Casino",
       });
 
       setEligibility(eligCheck);
@@ -246,7 +249,39 @@ export default function CasinoGame({
         "3AydpLAJiUVjMhxddULZjZNN2XJYEXE2cFezC1iiWcWH1GKxAF"
       );
 
-      // 🆕 STEP 1: Transfer PLT to platform (real money!)
+      // 🆕 STEP 1: Record transaction in registry (the contract can refuse it)
+      console.log("📝 Recording transaction in SafeStake registry...");
+      const recordResult = await sdk.recordTransactionWithBrowserWallet(
+        {
+          userAccount,
+          amountCCD: amount,
+          operatorAccount: SDKAccountAddress.fromBase58(OPERATOR_ACCOUNT),
+          platformCommitment: await platformCommitment(
+            account,
+            "safestake-casino"
+          ),
+          category: "Casino",
+        },
+        walletSigner
+      );
+
+      if (!recordResult.success) {
+        clearInterval(rollInterval);
+        setIsRolling(false);
+        alert(
+          recordResult.outcome
+            ? "🚫 Bet refused by SafeStake: " + recordResult.outcome
+            : "❌ Registry update failed: " + recordResult.error
+        );
+        return;
+      }
+
+      console.log(
+        "✅ Transaction recorded! TxHash:",
+        recordResult.transactionHash
+      );
+
+      // 🆕 STEP 2: Transfer PLT to platform (real money!)
       console.log("💸 Transferring bet amount to platform...");
       const pltResult = await sdk.executePLTTransfer(
         {
@@ -267,45 +302,25 @@ export default function CasinoGame({
 
       console.log("✅ PLT transferred! TxHash:", pltResult.transactionHash);
 
-      // 🆕 STEP 2: Record transaction in registry
-      console.log("📝 Recording transaction in SafeStake registry...");
-      const recordResult = await sdk.recordTransactionWithBrowserWallet(
-        { userAccount, amountCCD: amount },
-        walletSigner
-      );
-
-      if (recordResult.success) {
-        console.log(
-          "✅ Transaction recorded! TxHash:",
-          recordResult.transactionHash
-        );
-
-        // Final dice result
-        setTimeout(() => {
-          const finalRoll = Math.floor(Math.random() * 6) + 1;
-          setDiceResult(finalRoll);
-          setIsRolling(false);
-
-          const won = finalRoll >= prediction;
-          setLastWin(won);
-          setTotalBets((prev) => prev + 1);
-          setTotalWagered((prev) => prev + amount);
-          if (won) {
-            setTotalWins((prev) => prev + 1);
-
-            //  platform would send winnings back via PLT but i dun have any to send
-            console.log(
-              `🎉 Won! Platform would send ${amount * multiplier} CCD back`
-            );
-          }
-        }, 1200);
-      } else {
-        clearInterval(rollInterval);
+      // Final dice result
+      setTimeout(() => {
+        const finalRoll = Math.floor(Math.random() * 6) + 1;
+        setDiceResult(finalRoll);
         setIsRolling(false);
-        alert(
-          "⚠️ PLT transferred but registry update failed: " + recordResult.error
-        );
-      }
+
+        const won = finalRoll >= prediction;
+        setLastWin(won);
+        setTotalBets((prev) => prev + 1);
+        setTotalWagered((prev) => prev + amount);
+        if (won) {
+          setTotalWins((prev) => prev + 1);
+
+          //  platform would send winnings back via PLT but i dun have any to send
+          console.log(
+            `🎉 Won! Platform would send ${amount * multiplier} CCD back`
+          );
+        }
+      }, 1200);
     } catch (err) {
       setIsRolling(false);
       alert(err instanceof Error ? err.message : "Bet failed");
@@ -15,6 +15,12 @@ export const VERIFIER_BACKEND_URL =
 
 export const CONTEXT_STRING = "SafeStakeCasinoVerification";
 
+// Operator account registered for this platform in the registry. Bets are
+// recorded from this account, so the demo only records them when the
+// connected wallet holds it; a live platform records from its backend.
+export const OPERATOR_ACCOUNT =
+  "3AydpLAJiUVjMhxddULZjZNN2XJYEXE2cFezC1iiWcWH1GKxAF";
+
 export const BROWSER_WALLET = ephemeralConnectorType(
   BrowserWalletConnector.create
 );
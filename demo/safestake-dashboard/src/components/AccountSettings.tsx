@@ -61,6 +61,8 @@ export default function AccountSettings({
   // Limit settings
   const [dailyLimit, setDailyLimit] = useState("200");
   const [monthlyLimit, setMonthlyLimit] = useState("1000");
+  const [maxStake, setMaxStake] = useState("");
+  const [maxBetsPerDay, setMaxBetsPerDay] = useState("");
 
   // Notification preferences
   const [emailNotifications, setEmailNotifications] = useState(true);
@@ -106,6 +108,19 @@ export default function AccountSettings({
       return;
     }
 
+    // Left empty, these limits are not set
+    const stake = maxStake === "" ? undefined : parseFloat(maxStake);
+    const betsPerDay =
+      maxBetsPerDay === "" ? undefined : parseInt(maxBetsPerDay, 10);
+
+    if (
+      (stake !== undefined && (isNaN(stake) || stake <= 0)) ||
+      (betsPerDay !== undefined && (isNaN(betsPerDay) || betsPerDay <= 0))
+    ) {
+      alert("Please enter valid optional limits or leave them empty");
+      return;
+    }
+
     try {
       setLoading(true);
       const userAccount = SDKAccountAddress.fromBase58(account);
@@ -116,6 +131,8 @@ export default function AccountSettings({
           userAccount,
           dailyLimitCCD: daily,
           monthlyLimitCCD: monthly,
+          maxStakeCCD: stake,
+          maxBetsPerDay: betsPerDay,
         },
         walletSigner
       );
@@ -212,6 +229,40 @@ export default function AccountSettings({
                 Maximum amount you can wager per month
               </p>
             </div>
+
+            <div className="space-y-2">
+              <Label htmlFor="max-stake">Max Stake per Bet (CCD)</Label>
+              <Input
+                id="max-stake"
+                type="number"
+                value={maxStake}
+                onChange={(e) => setMaxStake(e.target.value)}
+                disabled={loading}
+                min="1"
+                step="5"
+                placeholder="No limit"
+              />
+              <p className="text-xs text-muted-foreground">
+                Largest single bet you can place
+              </p>
+            </div>
+
+            <div className="space-y-2">
+              <Label htmlFor="max-bets">Max Bets per Day</Label>
+              <Input
+                id="max-bets"
+                type="number"
+                value={maxBetsPerDay}
+                onChange={(e) => setMaxBetsPerDay(e.target.value)}
+                disabled={loading}
+                min="1"
+                step="1"
+                placeholder="No limit"
+              />
+              <p className="text-xs text-muted-foreground">
+                Number of bets you can place per day
+              </p>
+            </div>
           </div>
 
           <Button
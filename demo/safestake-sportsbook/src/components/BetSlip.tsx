@@ -25,8 +25,10 @@ import {
   CONTRACT_SUBINDEX,
   NODE_ADDRESS,
   NODE_PORT,
+  OPERATOR_ACCOUNT,
   VERIFIER_BACKEND_URL,
 } from "../config";
+import { platformCommitment } from "@/lib/platformCommitment";
 import type { Match } from "./MatchDisplay";
 
 const PLATFORM_ACCOUNT = "3AydpLAJiUVjMhxddULZjZNN2XJYEXE2cFezC1iiWcWH1GKxAF";
@@ -123,6 +125,7 @@ export default function BetSlip({
         const result = await sdk.checkEligibility({
           userAccount,
           proposedAmountCCD: stake,
+          category: "Sports",
         });
         setEligibility(result);
       } catch (err) {
@@ -150,59 +153,70 @@ export default function BetSlip({
       const platformAccount = SDKAccountAddress.fromBase58(PLATFORM_ACCOUNT);
       const walletSigner = createWalletSigner();
 
-      // 🆕 STEP 1: Transfer PLT to platform (real money!)
-      console.log("💸 Transferring bet amount to platform...");
-      const pltResult = await sdk.executePLTTransfer(
+      // 🆕 STEP 1: Record bet in SafeStake registry (the contract can refuse it)
+      console.log("📝 Recording bet in SafeStake registry...");
+      const recordResult = await sdk.recordTransactionWithBrowserWallet(
         {
           userAccount,
-          platformAccount,
           amountCCD: stake,
+          operatorAccount: SDKAccountAddress.fromBase58(OPERATOR_ACCOUNT),
+          platformCommitment: await platformCommitment(
+            account,
+            "safestake-sportsbook"
+          ),
+          category: "Sports",
         },
         walletSigner
       );
 
-      if (!pltResult.success) {
+      if (!recordResult.success) {
         setLastBetResult({
           success: false,
-          message: "PLT transfer failed: " + pltResult.error,
+          message: recordResult.outcome
+            ? "Bet refused by SafeStake: " + recordResult.outcome
+            : "Registry update failed: " + recordResult.error,
         });
         return;
       }
 
-      console.log("✅ PLT transferred! TxHash:", pltResult.transactionHash);
+      console.log("✅ Bet recorded! TxHash:", recordResult.transactionHash);
 
-      // 🆕 STEP 2: Record transaction in SafeStake registry
-      console.log("📝 Recording bet in SafeStake registry...");
-      const recordResult = await sdk.recordTransactionWithBrowserWallet(
-        { userAccount, amountCCD: stake },
+      // 🆕 STEP 2: Transfer PLT to platform (real money!)
+      console.log("💸 Transferring bet amount to platform...");
+      const pltResult = await sdk.executePLTTransfer(
+        {
+          userAccount,
+          platformAccount,
+          amountCCD: stake,
+        },
         walletSigner
       );
 
-      if (recordResult.success) {
-        console.log("✅ Bet recorded! TxHash:", recordResult.transactionHash);
-
-        setLastBetResult({
-          success: true,
-          message: "Bet placed successfully!",
-          pltHash: pltResult.transactionHash,
-          registryHash: recordResult.transactionHash,
-        });
-
-        // Clear bet slip after successful bet
-        setTimeout(() => {
-          onClearBets();
-          setStakeAmount("10");
-          setLastBetResult(null);
-        }, 5000);
-      } else {
+      if (!pltResult.success) {
         setLastBetResult({
           success: false,
           message:
-            "⚠️ PLT transferred but registry update failed: " +
-            recordResult.error,
-          pltHash: pltResult.transactionHash,
+            "⚠️ Bet recorded but PLT transfer failed: " + pltResult.error,
+          registryHash: recordResult.transactionHash,
         });
+        return;
       }
+
+      console.log("✅ PLT transferred! TxHash:", pltResult.transactionHash);
+
+      setLastBetResult({
+        success: true,
+        message: "Bet placed successfully!",
+        pltHash: pltResult.transactionHash,
+        registryHash: recordResult.transactionHash,
+      });
+
+      // Clear bet slip after successful bet
+      setTimeout(() => {
+        onClearBets();
+        setStakeAmount("10");
+        setLastBetResult(null);
+      }, 5000);
     } catch (err) {
       setLastBetResult({
         success: false,
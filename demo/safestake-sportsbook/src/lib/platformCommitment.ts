@@ -0,0 +1,26 @@
+import { computePlatformCommitment } from "@safestake/operator-sdk/browser";
+
+const SALT_KEY_PREFIX = "safestake-platform-salt:";
+
+// Salt the user's wallet keeps for its platform commitments. The demo keeps
+// one per account in localStorage; it never leaves the browser.
+function platformSalt(account: string): string {
+  const key = SALT_KEY_PREFIX + account;
+  let salt = localStorage.getItem(key);
+  if (!salt) {
+    const bytes = crypto.getRandomValues(new Uint8Array(32));
+    salt = Array.from(bytes, (byte) => byte.toString(16).padStart(2, "0")).join(
+      ""
+    );
+    localStorage.setItem(key, salt);
+  }
+  return salt;
+}
+
+// Commitment to the platform recorded with each bet instead of its ID
+export function platformCommitment(
+  account: string,
+  platformId: string
+): Promise<string> {
+  return computePlatformCommitment(platformSalt(account), platformId);
+}
@@ -5,7 +5,11 @@
  * This demonstrates the complete flow including signature handling
  */
 
-import { SafeStakeSDK, VerifierClient } from "../dist/index.js";
+import {
+  SafeStakeSDK,
+  VerifierClient,
+  computePlatformCommitment,
+} from "../dist/index.js";
 import {
   AccountAddress,
   ContractAddress,
@@ -235,10 +239,18 @@ async function runQuickTest() {
 
     console.log("💸 Step 7: Attempting to record transaction...");
 
+    // The test account doubles as the platform's operator, and a fixed salt
+    // stands in for the one the user's wallet would keep
     const recordResult = await sdk.recordTransaction(
       {
         userAccount: testAccount,
         amountCCD: 10,
+        operatorAccount: testAccount,
+        platformCommitment: await computePlatformCommitment(
+          "00".repeat(32),
+          "quick-test"
+        ),
+        category: "Casino",
       },
       signer
     );
@@ -246,6 +258,9 @@ async function runQuickTest() {
     if (recordResult.success) {
       console.log("✅ Transaction recorded");
       console.log(`   Transaction Hash: ${recordResult.transactionHash}\n`);
+    } else if (recordResult.outcome) {
+      console.log("🚫 Bet rejected by the contract");
+      console.log(`   Outcome: ${recordResult.outcome}\n`);
     } else {
       console.log("⚠️  Transaction recording failed");
       console.log(`   Error: ${recordResult.error}\n`);
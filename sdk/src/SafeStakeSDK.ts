@@ -36,9 +36,19 @@ import type {
   RecordTransactionResult,
   RegisterUserRequest,
   RegisterUserResult,
+  SetLimitsRequest,
   AgeVerificationResult,
   VerifiablePresentation,
 } from "./types.js";
+import { ContractTransactionOutcome } from "./types.js";
+import {
+  setLimitsParams,
+  checkEligibilityParams,
+  recordTransactionParams,
+  parseEligibilityResponse,
+  parseTransactionOutcome,
+  rejectedBetOutcome,
+} from "./contract.js";
 import { VerifierClient } from "./VerifierClient.js";
 
 /**
@@ -325,11 +335,7 @@ export class SafeStakeSDK {
    * Set spending limits for a user
    */
   async setLimits(
-    request: {
-      userAccount: AccountAddress.Type;
-      dailyLimitCCD: number;
-      monthlyLimitCCD: number;
-    },
+    request: SetLimitsRequest,
     signer: AccountSigner
   ): Promise<{ success: boolean; transactionHash?: string; error?: string }> {
     try {
@@ -337,17 +343,7 @@ export class SafeStakeSDK {
         throw new Error("SDK not initialized. Call initialize() first.");
       }
 
-      const dailyLimitMicroCCD = Math.floor(
-        request.dailyLimitCCD * 1_000_000
-      ).toString();
-      const monthlyLimitMicroCCD = Math.floor(
-        request.monthlyLimitCCD * 1_000_000
-      ).toString();
-
-      const params = {
-        daily_limit: dailyLimitMicroCCD,
-        monthly_limit: monthlyLimitMicroCCD,
-      };
+      const params = setLimitsParams(request);
 
       const parameter = serializeUpdateContractParameters(
         this.contractName,
@@ -413,14 +409,12 @@ export class SafeStakeSDK {
         throw new Error("SDK not initialized. Call initialize() first.");
       }
 
-      const amountMicroCCD = Math.floor(
-        request.proposedAmountCCD * 1_000_000
-      ).toString();
-
-      const params = {
-        user_account: request.userAccount.address,
-        proposed_amount: amountMicroCCD,
-      };
+      const params = checkEligibilityParams(
+        request.userAccount.address,
+        request.proposedAmountCCD,
+        this.config.platformId,
+        request.category
+      );
 
       const parameter = serializeUpdateContractParameters(
         this.contractName,
@@ -447,6 +441,9 @@ export class SafeStakeSDK {
 
   /**
    * Record a gambling transaction
+   * Sent by the operator account registered for this platform. A bet the
+   * contract refuses is still recorded as an attempt; it is reported with
+   * success false and the contract's outcome.
    */
   async recordTransaction(
     request: RecordTransactionRequest,
@@ -457,15 +454,12 @@ export class SafeStakeSDK {
         throw new Error("SDK not initialized. Call initialize() first.");
       }
 
-      const amountMicroCCD = Math.floor(
-        request.amountCCD * 1_000_000
-      ).toString();
-
-      const params = {
-        user_account: request.userAccount.address,
-        amount: amountMicroCCD,
-        platform_id: this.config.platformId,
-      };
+      const params = recordTransactionParams(
+        request.userAccount.address,
+        request.amountCCD,
+        request.platformCommitment,
+        request.category
+      );
 
       const parameter = serializeUpdateContractParameters(
         this.contractName,
@@ -478,8 +472,23 @@ export class SafeStakeSDK {
         `${this.contractName.value}.record_transaction`
       );
 
+      // The contract keeps nothing for a user who is not registered, so
+      // there is nothing to send
+      const preview = await this.previewOutcome(
+        request.operatorAccount,
+        receiveName,
+        parameter
+      );
+      if (preview === ContractTransactionOutcome.NotRegistered) {
+        return {
+          success: false,
+          outcome: preview,
+          error: "Bet rejected: NotRegistered",
+        };
+      }
+
       const txHash = await this.sendContractUpdate(
-        request.userAccount,
+        request.operatorAccount,
         receiveName,
         parameter,
         signer
@@ -506,8 +515,19 @@ export class SafeStakeSDK {
         };
       }
 
+      const rejected = rejectedBetOutcome(status.summary);
+      if (rejected) {
+        return {
+          success: false,
+          outcome: rejected,
+          transactionHash: txHash.toString(),
+          error: `Bet rejected: ${rejected}`,
+        };
+      }
+
       return {
         success: true,
+        outcome: ContractTransactionOutcome.Recorded,
         transactionHash: txHash.toString(),
       };
     } catch (error) {
@@ -654,6 +674,41 @@ export class SafeStakeSDK {
     return txHash;
   }
 
+  /**
+   * Outcome record_transaction would return if sent now by the operator,
+   * or undefined if the invocation fails
+   */
+  private async previewOutcome(
+    operatorAccount: AccountAddress.Type,
+    method: ReceiveName.Type,
+    parameter: Parameter.Type
+  ): Promise<ContractTransactionOutcome | undefined> {
+    const result = await this.client.invokeContract({
+      contract: this.config.contractAddress,
+      invoker: operatorAccount,
+      method,
+      parameter,
+    });
+    if (result.tag === "failure" || !result.returnValue) {
+      return undefined;
+    }
+
+    const returnValueUint8 = ReturnValue.toBuffer(result.returnValue);
+    const returnValueBuffer = returnValueUint8.buffer.slice(
+      returnValueUint8.byteOffset,
+      returnValueUint8.byteOffset + returnValueUint8.byteLength
+    ) as ArrayBuffer;
+
+    return parseTransactionOutcome(
+      deserializeReceiveReturnValue(
+        returnValueBuffer,
+        this.moduleSchema!,
+        this.contractName,
+        EntrypointName.fromString("record_transaction")
+      )
+    );
+  }
+
   /**
    * Parse the eligibility result from contract invocation
    */
@@ -692,56 +747,7 @@ export class SafeStakeSDK {
         EntrypointName.fromString("check_eligibility")
       );
 
-      const status = deserializedValue as any;
-
-      if (status.Eligible !== undefined) {
-        return {
-          eligible: true,
-          message: "User is eligible to place this bet",
-        };
-      } else if (status.NotRegistered !== undefined) {
-        return {
-          eligible: false,
-          reason: "not_registered",
-          message: "User is not registered",
-        };
-      } else if (status.DailyLimitReached !== undefined) {
-        return {
-          eligible: false,
-          reason: "daily_limit",
-          message: "Daily spending limit would be exceeded",
-        };
-      } else if (status.MonthlyLimitReached !== undefined) {
-        return {
-          eligible: false,
-          reason: "monthly_limit",
-          message: "Monthly spending limit would be exceeded",
-        };
-      } else if (status.SelfExcluded !== undefined) {
-        return {
-          eligible: false,
-          reason: "self_excluded",
-          message: "User is currently self-excluded",
-        };
-      } else if (status.OnCooldown !== undefined) {
-        return {
-          eligible: false,
-          reason: "on_cooldown",
-          message: "User is in cooldown period",
-        };
-      } else if (status.AgeNotVerified !== undefined) {
-        return {
-          eligible: false,
-          reason: "age_not_verified",
-          message: "User has not completed age verification",
-        };
-      }
-
-      return {
-        eligible: false,
-        reason: "not_registered",
-        message: "Unknown eligibility status",
-      };
+      return parseEligibilityResponse(deserializedValue);
     } catch (error) {
       console.error("Failed to parse eligibility result:", error);
       return {
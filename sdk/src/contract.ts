@@ -0,0 +1,295 @@
+/**
+ * SafeStake contract parameters and results
+ *
+ * Shared by the Node.js and browser SDKs, so both build the same parameter
+ * layouts and read the contract's answers the same way
+ */
+
+import { ContractEvent } from "@concordium/web-sdk";
+import {
+  ContractEligibilityStatus,
+  ContractTransactionOutcome,
+} from "./types.js";
+import type {
+  EligibilityCheckResult,
+  GameCategory,
+  SetLimitsRequest,
+} from "./types.js";
+
+/**
+ * Convert CCD to the microCCD string the schema expects for an Amount
+ */
+function toMicroCCD(amountCCD: number): string {
+  return Math.floor(amountCCD * 1_000_000).toString();
+}
+
+/**
+ * Convert a microCCD string from the contract to CCD
+ */
+function fromMicroCCD(microCCD: string): number {
+  return Number(microCCD) / 1_000_000;
+}
+
+/**
+ * Schema JSON for an Option
+ */
+function option<T>(value: T | undefined): { None: [] } | { Some: [T] } {
+  return value === undefined ? { None: [] } : { Some: [value] };
+}
+
+/**
+ * Name of the variant in the schema JSON of a contract enum
+ */
+function variant(value: any): string {
+  return Object.keys(value)[0];
+}
+
+/**
+ * Parameter for set_limits
+ */
+export function setLimitsParams(request: SetLimitsRequest) {
+  return {
+    daily_limit: toMicroCCD(request.dailyLimitCCD),
+    monthly_limit: toMicroCCD(request.monthlyLimitCCD),
+    max_stake: option(
+      request.maxStakeCCD === undefined
+        ? undefined
+        : toMicroCCD(request.maxStakeCCD)
+    ),
+    max_bets_per_hour: option(request.maxBetsPerHour),
+    max_bets_per_day: option(request.maxBetsPerDay),
+    daily_time_limit: option(
+      request.dailyTimeLimitMinutes === undefined
+        ? undefined
+        : `${request.dailyTimeLimitMinutes}m`
+    ),
+  };
+}
+
+/**
+ * Parameter for check_eligibility on the given platform
+ */
+export function checkEligibilityParams(
+  userAccount: string,
+  proposedAmountCCD: number,
+  platformId: string,
+  category?: GameCategory
+) {
+  return {
+    user_account: userAccount,
+    proposed_amount: toMicroCCD(proposedAmountCCD),
+    platform_id: option(platformId),
+    category: option(category === undefined ? undefined : { [category]: [] }),
+  };
+}
+
+/**
+ * Parameter for record_transaction
+ */
+export function recordTransactionParams(
+  userAccount: string,
+  amountCCD: number,
+  platformCommitment: string,
+  category: GameCategory
+) {
+  if (!/^[0-9a-fA-F]{64}$/.test(platformCommitment)) {
+    throw new Error(
+      "Invalid platform commitment: must be 64 hex characters (32 bytes)"
+    );
+  }
+
+  return {
+    user_account: userAccount,
+    amount: toMicroCCD(amountCCD),
+    platform_commitment: Array.from(
+      { length: 32 },
+      (_, i) => parseInt(platformCommitment.slice(i * 2, i * 2 + 2), 16)
+    ),
+    category: { [category]: [] },
+  };
+}
+
+/**
+ * Commitment to a platform as the user's wallet computes it for operators:
+ * sha256(salt || platform ID), hex-encoded. The salt is the user's own and is
+ * never sent to the contract.
+ */
+export async function computePlatformCommitment(
+  saltHex: string,
+  platformId: string
+): Promise<string> {
+  const salt = Uint8Array.from(
+    saltHex.match(/.{2}/g) ?? [],
+    (byte) => parseInt(byte, 16)
+  );
+  const id = new TextEncoder().encode(platformId);
+  const data = new Uint8Array(salt.length + id.length);
+  data.set(salt);
+  data.set(id, salt.length);
+
+  const digest = await crypto.subtle.digest("SHA-256", data);
+  return Array.from(new Uint8Array(digest), (byte) =>
+    byte.toString(16).padStart(2, "0")
+  ).join("");
+}
+
+const ELIGIBILITY_REASONS: Partial<
+  Record<
+    ContractEligibilityStatus,
+    [NonNullable<EligibilityCheckResult["reason"]>, string]
+  >
+> = {
+  [ContractEligibilityStatus.NotRegistered]: [
+    "not_registered",
+    "User is not registered",
+  ],
+  [ContractEligibilityStatus.DailyLimitReached]: [
+    "daily_limit",
+    "Daily spending limit would be exceeded",
+  ],
+  [ContractEligibilityStatus.MonthlyLimitReached]: [
+    "monthly_limit",
+    "Monthly spending limit would be exceeded",
+  ],
+  [ContractEligibilityStatus.SelfExcluded]: [
+    "self_excluded",
+    "User is currently self-excluded",
+  ],
+  [ContractEligibilityStatus.OnCooldown]: [
+    "on_cooldown",
+    "User is in cooldown period",
+  ],
+  [ContractEligibilityStatus.AgeNotVerified]: [
+    "age_not_verified",
+    "User has not completed age verification",
+  ],
+  [ContractEligibilityStatus.StakeTooHigh]: [
+    "stake_too_high",
+    "Bet is larger than the maximum allowed stake",
+  ],
+  [ContractEligibilityStatus.BetCountLimitReached]: [
+    "bet_count_limit",
+    "User has placed the maximum number of bets for now",
+  ],
+  [ContractEligibilityStatus.SessionTimeLimitReached]: [
+    "session_time_limit",
+    "User has used up their daily play time",
+  ],
+  [ContractEligibilityStatus.PlatformDailyLimitReached]: [
+    "platform_daily_limit",
+    "Daily spending limit on this platform would be exceeded",
+  ],
+  [ContractEligibilityStatus.PlatformMonthlyLimitReached]: [
+    "platform_monthly_limit",
+    "Monthly spending limit on this platform would be exceeded",
+  ],
+  [ContractEligibilityStatus.PlatformExcluded]: [
+    "platform_excluded",
+    "User is excluded from this platform",
+  ],
+  [ContractEligibilityStatus.CategoryDailyLimitReached]: [
+    "category_daily_limit",
+    "Daily spending limit on this game category would be exceeded",
+  ],
+  [ContractEligibilityStatus.CategoryMonthlyLimitReached]: [
+    "category_monthly_limit",
+    "Monthly spending limit on this game category would be exceeded",
+  ],
+  [ContractEligibilityStatus.CategoryExcluded]: [
+    "category_excluded",
+    "User is excluded from this game category",
+  ],
+  [ContractEligibilityStatus.AffordabilityCheckRequired]: [
+    "affordability_check_required",
+    "Bet needs an affordability check first",
+  ],
+  [ContractEligibilityStatus.PendingReactivation]: [
+    "pending_reactivation",
+    "User has not completed reactivation after a self-exclusion",
+  ],
+  [ContractEligibilityStatus.TransactionsPaused]: [
+    "transactions_paused",
+    "Recording of bets is paused",
+  ],
+};
+
+/**
+ * Read the EligibilityResponse returned by check_eligibility
+ */
+export function parseEligibilityResponse(
+  response: any
+): EligibilityCheckResult {
+  const status = variant(response.status) as ContractEligibilityStatus;
+  const blockedUntil = response.blocked_until?.Some?.[0];
+  const details = {
+    remainingDailyLimitCCD: fromMicroCCD(response.remaining_daily),
+    remainingMonthlyLimitCCD: fromMicroCCD(response.remaining_monthly),
+    maxEligibleStakeCCD: fromMicroCCD(response.max_eligible_stake),
+    blockedUntil: blockedUntil ? new Date(blockedUntil) : undefined,
+  };
+
+  if (status === ContractEligibilityStatus.Eligible) {
+    return {
+      eligible: true,
+      message: "User is eligible to place this bet",
+      ...details,
+    };
+  }
+  if (status === ContractEligibilityStatus.ReviewRequired) {
+    return {
+      eligible: true,
+      reviewRequired: true,
+      message: "User is eligible, but their risk score calls for a review",
+      ...details,
+    };
+  }
+
+  const [reason, message] = ELIGIBILITY_REASONS[status] ?? [
+    "not_registered",
+    "Unknown eligibility status",
+  ];
+  return { eligible: false, reason, message, ...details };
+}
+
+/**
+ * Read the TransactionOutcome returned by record_transaction
+ */
+export function parseTransactionOutcome(
+  outcome: any
+): ContractTransactionOutcome {
+  return variant(outcome) as ContractTransactionOutcome;
+}
+
+// Tag of Event::BetRejected, the contract's second event
+const BET_REJECTED_TAG = 1;
+
+// Byte of the outcome in a BetRejected event: after the tag, the user's
+// account address (32 bytes) and the amount (8 bytes)
+const BET_REJECTED_OUTCOME_OFFSET = 41;
+
+/**
+ * Outcome of a refused bet, read from the BetRejected event the contract logs
+ * in the finalized transaction. Undefined when the transaction logged none,
+ * i.e. the bet was recorded (or the user is not registered).
+ */
+export function rejectedBetOutcome(
+  summary: any
+): ContractTransactionOutcome | undefined {
+  const outcomes = Object.values(ContractTransactionOutcome);
+
+  for (const traceEvent of summary.events ?? []) {
+    if (traceEvent.tag !== "Updated") {
+      continue;
+    }
+    for (const event of traceEvent.events ?? []) {
+      const hex =
+        typeof event === "string" ? event : ContractEvent.toHexString(event);
+      const tag = parseInt(hex.slice(0, 2), 16);
+      if (tag === BET_REJECTED_TAG) {
+        const offset = BET_REJECTED_OUTCOME_OFFSET * 2;
+        return outcomes[parseInt(hex.slice(offset, offset + 2), 16)];
+      }
+    }
+  }
+  return undefined;
+}
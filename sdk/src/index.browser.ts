@@ -16,6 +16,8 @@ export type {
   EligibilityCheckResult,
   RecordTransactionRequest,
   RecordTransactionResult,
+  SetLimitsRequest,
+  GameCategory,
   RegisterUserRequest,
   RegisterUserResult,
   UserComplianceData,
@@ -27,7 +29,12 @@ export type {
   VerifierErrorResponse,
 } from "./types.js";
 
-export { ContractEligibilityStatus } from "./types.js";
+export {
+  ContractEligibilityStatus,
+  ContractTransactionOutcome,
+} from "./types.js";
+
+export { computePlatformCommitment } from "./contract.js";
 
 export {
   AccountAddress,
@@ -22,6 +22,8 @@ export type {
   EligibilityCheckResult,
   RecordTransactionRequest,
   RecordTransactionResult,
+  SetLimitsRequest,
+  GameCategory,
   RegisterUserRequest,
   RegisterUserResult,
   UserComplianceData,
@@ -34,7 +36,12 @@ export type {
   VerifierErrorResponse,
 } from "./types.js";
 
-export { ContractEligibilityStatus } from "./types.js";
+export {
+  ContractEligibilityStatus,
+  ContractTransactionOutcome,
+} from "./types.js";
+
+export { computePlatformCommitment } from "./contract.js";
 
 // Re-export commonly used Concordium types for convenience
 export {
@@ -32,8 +32,47 @@ export interface SafeStakeConfig {
   verifierBackendUrl?: string;
 }
 
+/**
+ * Kind of game a bet is placed on (matches Rust contract)
+ */
+export type GameCategory =
+  | "Sports"
+  | "Casino"
+  | "Slots"
+  | "Poker"
+  | "Lottery"
+  | "Bingo"
+  | "Other";
+
+/**
+ * Spending limits request
+ */
+export interface SetLimitsRequest {
+  /** User's Concordium account address (must send the transaction) */
+  userAccount: AccountAddress.Type;
+
+  /** Daily spending limit in CCD */
+  dailyLimitCCD: number;
+
+  /** Monthly spending limit in CCD */
+  monthlyLimitCCD: number;
+
+  /** Maximum stake on a single bet in CCD (default: no limit) */
+  maxStakeCCD?: number;
+
+  /** Maximum number of bets per hour (default: no limit) */
+  maxBetsPerHour?: number;
+
+  /** Maximum number of bets per day (default: no limit) */
+  maxBetsPerDay?: number;
+
+  /** Maximum play time per day across all platforms (default: no limit) */
+  dailyTimeLimitMinutes?: number;
+}
+
 /**
  * Eligibility check request
+ * The bet is checked on the SDK's configured platform.
  */
 export interface EligibilityCheckRequest {
   /** User's Concordium account address */
@@ -41,6 +80,9 @@ export interface EligibilityCheckRequest {
 
   /** Proposed bet amount in CCD */
   proposedAmountCCD: number;
+
+  /** Kind of game, to include the user's limits on it (optional) */
+  category?: GameCategory;
 }
 
 /**
@@ -57,7 +99,25 @@ export interface EligibilityCheckResult {
     | "monthly_limit"
     | "self_excluded"
     | "on_cooldown"
-    | "age_not_verified";
+    | "age_not_verified"
+    | "stake_too_high"
+    | "bet_count_limit"
+    | "session_time_limit"
+    | "platform_daily_limit"
+    | "platform_monthly_limit"
+    | "platform_excluded"
+    | "category_daily_limit"
+    | "category_monthly_limit"
+    | "category_excluded"
+    | "affordability_check_required"
+    | "pending_reactivation"
+    | "transactions_paused";
+
+  /**
+   * Whether the user's risk score calls for a review. Advisory only: the
+   * user is still eligible, and the platform decides what a review means.
+   */
+  reviewRequired?: boolean;
 
   /** Remaining daily limit in CCD */
   remainingDailyLimitCCD?: number;
@@ -65,12 +125,20 @@ export interface EligibilityCheckResult {
   /** Remaining monthly limit in CCD */
   remainingMonthlyLimitCCD?: number;
 
+  /** Largest bet that would currently be eligible in CCD */
+  maxEligibleStakeCCD?: number;
+
+  /** When the blocking condition ends, if it ends */
+  blockedUntil?: Date;
+
   /** Human-readable message */
   message: string;
 }
 
 /**
  * Transaction recording request
+ * Only a registered operator may record bets, and they count on the platform
+ * the operator is registered for.
  */
 export interface RecordTransactionRequest {
   /** User's Concordium account address */
@@ -78,15 +146,34 @@ export interface RecordTransactionRequest {
 
   /** Transaction amount in CCD */
   amountCCD: number;
+
+  /** Operator account registered for this platform (sends the transaction) */
+  operatorAccount: AccountAddress.Type;
+
+  /**
+   * Commitment to this platform from the user's wallet (hex, 64 characters):
+   * sha256(salt || platform ID) with the user's own salt.
+   * See computePlatformCommitment()
+   */
+  platformCommitment: string;
+
+  /** Kind of game the bet is placed on */
+  category: GameCategory;
 }
 
 /**
  * Transaction recording response
  */
 export interface RecordTransactionResult {
-  /** Whether the transaction was recorded successfully */
+  /** Whether the bet was recorded against the user's limits */
   success: boolean;
 
+  /**
+   * Contract's outcome for the bet. A refused bet is still a finalized
+   * transaction, so the contract can remember the attempt.
+   */
+  outcome?: ContractTransactionOutcome;
+
   /** Transaction hash on the blockchain */
   transactionHash?: string;
 
@@ -141,6 +228,43 @@ export enum ContractEligibilityStatus {
   OnCooldown = "OnCooldown",
   NotRegistered = "NotRegistered",
   AgeNotVerified = "AgeNotVerified",
+  StakeTooHigh = "StakeTooHigh",
+  BetCountLimitReached = "BetCountLimitReached",
+  SessionTimeLimitReached = "SessionTimeLimitReached",
+  PlatformDailyLimitReached = "PlatformDailyLimitReached",
+  PlatformMonthlyLimitReached = "PlatformMonthlyLimitReached",
+  PlatformExcluded = "PlatformExcluded",
+  CategoryDailyLimitReached = "CategoryDailyLimitReached",
+  CategoryMonthlyLimitReached = "CategoryMonthlyLimitReached",
+  CategoryExcluded = "CategoryExcluded",
+  ReviewRequired = "ReviewRequired",
+  AffordabilityCheckRequired = "AffordabilityCheckRequired",
+  PendingReactivation = "PendingReactivation",
+  TransactionsPaused = "TransactionsPaused",
+}
+
+/**
+ * Contract transaction outcome enum (matches Rust contract, in its order)
+ */
+export enum ContractTransactionOutcome {
+  Recorded = "Recorded",
+  DailyLimitExceeded = "DailyLimitExceeded",
+  MonthlyLimitExceeded = "MonthlyLimitExceeded",
+  SelfExcluded = "SelfExcluded",
+  OnCooldown = "OnCooldown",
+  NotRegistered = "NotRegistered",
+  AgeNotVerified = "AgeNotVerified",
+  StakeTooHigh = "StakeTooHigh",
+  BetCountLimitExceeded = "BetCountLimitExceeded",
+  SessionTimeLimitExceeded = "SessionTimeLimitExceeded",
+  PlatformDailyLimitExceeded = "PlatformDailyLimitExceeded",
+  PlatformMonthlyLimitExceeded = "PlatformMonthlyLimitExceeded",
+  PlatformExcluded = "PlatformExcluded",
+  CategoryDailyLimitExceeded = "CategoryDailyLimitExceeded",
+  CategoryMonthlyLimitExceeded = "CategoryMonthlyLimitExceeded",
+  CategoryExcluded = "CategoryExcluded",
+  AffordabilityCheckRequired = "AffordabilityCheckRequired",
+  PendingReactivation = "PendingReactivation",
 }
 
 /**
@@ -12,6 +12,14 @@ import {
 } from "@concordium/web-sdk";
 import { config } from "dotenv";
 import { VerifierClient } from "../src/verifierClient.js";
+import {
+  setLimitsParams,
+  recordTransactionParams,
+  parseEligibilityResponse,
+  rejectedBetOutcome,
+  computePlatformCommitment,
+} from "../src/contract.js";
+import { ContractTransactionOutcome } from "../src/types.js";
 config();
 
 describe("SafeStakeSDK with Age Verification", () => {
@@ -341,10 +349,17 @@ describe("SafeStakeSDK with Age Verification", () => {
 
   describe("Transaction Recording (Existing Functionality)", () => {
     test("should handle record transaction attempt", async () => {
+      // The test account acts as the operator registered for test-platform
       const result = await sdk.recordTransaction(
         {
           userAccount: testUserAccount,
           amountCCD: 10,
+          operatorAccount: testUserAccount,
+          platformCommitment: await computePlatformCommitment(
+            "00".repeat(32),
+            "test-platform"
+          ),
+          category: "Sports",
         },
         signer
       );
@@ -352,6 +367,7 @@ describe("SafeStakeSDK with Age Verification", () => {
       expect(result).toHaveProperty("success");
 
       if (result.success) {
+        expect(result.outcome).toBe(ContractTransactionOutcome.Recorded);
         console.log("✅ Transaction recorded:", result.transactionHash);
       } else {
         console.log(
@@ -390,3 +406,68 @@ describe("VerifierClient (Unit Tests)", () => {
     expect(/^[0-9a-fA-F]{128}$/.test(invalidSignature)).toBe(false);
   });
 });
+
+describe("Contract parameters and results (Unit Tests)", () => {
+  test("should fill every limit in set_limits", () => {
+    expect(
+      setLimitsParams({
+        userAccount: AccountAddress.fromBase58(
+          "4gSD7ho5jPyhAizicTbxWdPUYNvWwZXNTFMDuSgsX4rDLFYtHr"
+        ),
+        dailyLimitCCD: 100,
+        monthlyLimitCCD: 1000,
+        maxBetsPerDay: 20,
+      })
+    ).toEqual({
+      daily_limit: "100000000",
+      monthly_limit: "1000000000",
+      max_stake: { None: [] },
+      max_bets_per_hour: { None: [] },
+      max_bets_per_day: { Some: [20] },
+      daily_time_limit: { None: [] },
+    });
+  });
+
+  test("should reject a malformed platform commitment", () => {
+    expect(() =>
+      recordTransactionParams("account", 1, "abc", "Casino")
+    ).toThrow("64 hex characters");
+  });
+
+  test("should read eligibility details and advisory reviews", () => {
+    const response = {
+      status: { ReviewRequired: [] },
+      remaining_daily: "40000000",
+      remaining_monthly: "900000000",
+      max_eligible_stake: "40000000",
+      blocked_until: { None: [] },
+      exclusion: { None: [] },
+    };
+
+    const result = parseEligibilityResponse(response);
+    expect(result.eligible).toBe(true);
+    expect(result.reviewRequired).toBe(true);
+    expect(result.remainingDailyLimitCCD).toBe(40);
+    expect(result.maxEligibleStakeCCD).toBe(40);
+
+    const blocked = parseEligibilityResponse({
+      ...response,
+      status: { PlatformExcluded: [] },
+      blocked_until: { Some: ["2030-01-01T00:00:00Z"] },
+    });
+    expect(blocked.eligible).toBe(false);
+    expect(blocked.reason).toBe("platform_excluded");
+    expect(blocked.blockedUntil).toEqual(new Date("2030-01-01T00:00:00Z"));
+  });
+
+  test("should report a rejected bet from its BetRejected event", () => {
+    // Tag 1, 32-byte account, 8-byte amount, outcome 12 (PlatformExcluded)
+    const event = "01" + "00".repeat(32) + "00".repeat(8) + "0c";
+    const summary = { events: [{ tag: "Updated", events: [event] }] };
+
+    expect(rejectedBetOutcome(summary)).toBe(
+      ContractTransactionOutcome.PlatformExcluded
+    );
+    expect(rejectedBetOutcome({ events: [] })).toBeUndefined();
+  });
+});